@@ -0,0 +1,584 @@
+//! Bounding volume hierarchy over items with `BoundingSpaceN` bounds.
+//!
+//! Nodes are built top-down with binned SAH and stored depth first in a
+//! flat array: the left child of an interior node directly follows it and
+//! the node keeps the index of its right child. `build_linear` instead sorts
+//! the items along a Morton curve and splits at the highest differing key
+//! bit, trading tree quality for a much cheaper build.
+
+use nalgebra::{convert, try_convert, Point, RealField};
+
+use crate::curve::morton_key;
+use crate::{total_cmp, BoundingSpaceN, PrecomputedRay, Ray};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BvhConfig {
+    /// Largest number of items a leaf may hold.
+    pub max_leaf_size: usize,
+    /// Number of centroid bins evaluated per axis by SAH.
+    pub bins: usize,
+}
+
+impl Default for BvhConfig {
+    fn default() -> Self {
+        Self {
+            max_leaf_size: 4,
+            bins: 16,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BvhNode<T: RealField, const D: usize> {
+    bound: BoundingSpaceN<T, D>,
+    /// First item of a leaf or the right child of an interior node.
+    offset: usize,
+    /// Number of items in a leaf, zero for interior nodes.
+    count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bvh<T: RealField, const D: usize, Item> {
+    nodes: Vec<BvhNode<T, D>>,
+    bounds: Vec<BoundingSpaceN<T, D>>,
+    items: Vec<Item>,
+}
+
+/// Measure proportional to the chance of a random ray hitting the space.
+pub(crate) fn sah_measure<T: RealField, const D: usize>(bound: &BoundingSpaceN<T, D>) -> T {
+    if D == 1 {
+        bound.diagonal()[0].to_owned()
+    } else {
+        bound.surface_measure()
+    }
+}
+
+struct Builder<'a, T: RealField, const D: usize> {
+    config: &'a BvhConfig,
+    bounds: &'a [BoundingSpaceN<T, D>],
+    centers: Vec<Point<T, D>>,
+    order: Vec<usize>,
+    nodes: Vec<BvhNode<T, D>>,
+}
+
+impl<'a, T: RealField, const D: usize> Builder<'a, T, D> {
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let index = self.nodes.len();
+        let bound = self.order[start..end]
+            .iter()
+            .fold(BoundingSpaceN::empty(), |acc, &i| {
+                acc.union(&self.bounds[i])
+            });
+        self.nodes.push(BvhNode {
+            bound,
+            offset: start,
+            count: end - start,
+        });
+
+        if end - start <= 1 {
+            return index;
+        }
+
+        let mid = match self.sah_split(start, end, index) {
+            Some(mid) => mid,
+            None if end - start <= self.config.max_leaf_size => return index,
+            None => self.median_split(start, end),
+        };
+
+        self.build(start, mid);
+        let right = self.build(mid, end);
+        self.nodes[index].offset = right;
+        self.nodes[index].count = 0;
+
+        index
+    }
+
+    /// Splits a range of key-sorted items where the keys first differ,
+    /// halving ranges of equal keys.
+    fn build_linear(&mut self, keys: &[u128], start: usize, end: usize) -> usize {
+        let index = self.nodes.len();
+        let bound = self.order[start..end]
+            .iter()
+            .fold(BoundingSpaceN::empty(), |acc, &i| {
+                acc.union(&self.bounds[i])
+            });
+        self.nodes.push(BvhNode {
+            bound,
+            offset: start,
+            count: end - start,
+        });
+
+        if end - start <= self.config.max_leaf_size.max(1) {
+            return index;
+        }
+
+        let (first, last) = (keys[start], keys[end - 1]);
+        let mid = if first == last {
+            start + (end - start) / 2
+        } else {
+            let bit = 127 - (first ^ last).leading_zeros();
+            start + keys[start..end].partition_point(|key| key & (1 << bit) == 0)
+        };
+
+        self.build_linear(keys, start, mid);
+        let right = self.build_linear(keys, mid, end);
+        self.nodes[index].offset = right;
+        self.nodes[index].count = 0;
+
+        index
+    }
+
+    fn centroid_bound(&self, start: usize, end: usize) -> BoundingSpaceN<T, D> {
+        self.order[start..end]
+            .iter()
+            .map(|&i| &self.centers[i])
+            .collect()
+    }
+
+    fn bin_of(&self, item: usize, axis: usize, centroids: &BoundingSpaceN<T, D>) -> usize {
+        let bins = self.config.bins.max(2);
+        let extent = centroids.upper[axis].to_owned() - centroids.lower[axis].to_owned();
+        let relative = (self.centers[item][axis].to_owned() - centroids.lower[axis].to_owned())
+            / extent
+            * convert(bins as f64);
+        let bin = try_convert::<T, f64>(relative).unwrap_or(0.0) as usize;
+        bin.min(bins - 1)
+    }
+
+    /// Partition point of the cheapest binned SAH split, `None` when keeping
+    /// a leaf is cheaper or the centroids cannot be separated.
+    fn sah_split(&mut self, start: usize, end: usize, node: usize) -> Option<usize> {
+        let bins = self.config.bins.max(2);
+        let centroids = self.centroid_bound(start, end);
+        let mut best: Option<(T, usize, usize)> = None;
+
+        for axis in 0..D {
+            if centroids.upper[axis] <= centroids.lower[axis] {
+                continue;
+            }
+
+            let mut bin_bounds = vec![BoundingSpaceN::<T, D>::empty(); bins];
+            let mut bin_counts = vec![0usize; bins];
+            for &i in &self.order[start..end] {
+                let bin = self.bin_of(i, axis, &centroids);
+                bin_bounds[bin].merge(&self.bounds[i]);
+                bin_counts[bin] += 1;
+            }
+
+            let mut right_costs = vec![T::zero(); bins];
+            let mut accumulated = BoundingSpaceN::empty();
+            let mut count = 0;
+            for split in (1..bins).rev() {
+                accumulated.merge(&bin_bounds[split]);
+                count += bin_counts[split];
+                right_costs[split] = sah_measure(&accumulated) * convert(count as f64);
+            }
+
+            let mut accumulated = BoundingSpaceN::empty();
+            let mut count = 0;
+            for split in 1..bins {
+                accumulated.merge(&bin_bounds[split - 1]);
+                count += bin_counts[split - 1];
+                if count == 0 || count == end - start {
+                    continue;
+                }
+
+                let cost = sah_measure(&accumulated) * convert(count as f64)
+                    + right_costs[split].to_owned();
+                if best.as_ref().is_none_or(|(b, _, _)| cost < *b) {
+                    best = Some((cost, axis, split));
+                }
+            }
+        }
+
+        let (cost, axis, split) = best?;
+        let leaf_cost = sah_measure(&self.nodes[node].bound) * convert((end - start) as f64);
+        if end - start <= self.config.max_leaf_size && leaf_cost <= cost {
+            return None;
+        }
+
+        let mut order = core::mem::take(&mut self.order);
+        order[start..end].sort_by_key(|&i| self.bin_of(i, axis, &centroids) >= split);
+        self.order = order;
+
+        let left = self.order[start..end]
+            .iter()
+            .take_while(|&&i| self.bin_of(i, axis, &centroids) < split)
+            .count();
+        Some(start + left)
+    }
+
+    fn median_split(&mut self, start: usize, end: usize) -> usize {
+        let axis = self.centroid_bound(start, end).longest_axis();
+        let mid = start + (end - start) / 2;
+        let centers = &self.centers;
+
+        self.order[start..end].select_nth_unstable_by(mid - start, |&a, &b| {
+            total_cmp(&centers[a][axis], &centers[b][axis])
+        });
+
+        mid
+    }
+}
+
+impl<T: RealField, const D: usize, Item> Bvh<T, D, Item> {
+    pub fn build(entries: Vec<(BoundingSpaceN<T, D>, Item)>) -> Self {
+        Self::build_with(entries, &BvhConfig::default())
+    }
+
+    pub fn build_with(entries: Vec<(BoundingSpaceN<T, D>, Item)>, config: &BvhConfig) -> Self {
+        let (bounds, items): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+
+        let mut builder = Builder {
+            config,
+            bounds: &bounds,
+            centers: bounds.iter().map(|b| b.center()).collect(),
+            order: (0..bounds.len()).collect(),
+            nodes: Vec::new(),
+        };
+        if !bounds.is_empty() {
+            builder.build(0, bounds.len());
+        }
+
+        let Builder { order, nodes, .. } = builder;
+        Self::assemble(bounds, items, &order, nodes)
+    }
+
+    /// Linear BVH over items sorted by the Morton key of their centers; only
+    /// `max_leaf_size` of the configuration is used.
+    pub fn build_linear(entries: Vec<(BoundingSpaceN<T, D>, Item)>, config: &BvhConfig) -> Self {
+        let (bounds, items): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+        let centers: Vec<_> = bounds.iter().map(|b| b.center()).collect();
+        let reference: BoundingSpaceN<T, D> = centers.iter().collect();
+        let bits = (u128::BITS / D.max(1) as u32).clamp(1, 32);
+        let keys: Vec<u128> = centers
+            .iter()
+            .map(|c| morton_key(&reference, c, bits))
+            .collect();
+
+        let mut order: Vec<usize> = (0..bounds.len()).collect();
+        order.sort_by_key(|&i| keys[i]);
+        let sorted_keys: Vec<u128> = order.iter().map(|&i| keys[i]).collect();
+
+        let mut builder = Builder {
+            config,
+            bounds: &bounds,
+            centers,
+            order,
+            nodes: Vec::new(),
+        };
+        if !bounds.is_empty() {
+            builder.build_linear(&sorted_keys, 0, bounds.len());
+        }
+
+        let Builder { order, nodes, .. } = builder;
+        Self::assemble(bounds, items, &order, nodes)
+    }
+
+    /// Stores items in the leaf order chosen by a builder.
+    fn assemble(
+        bounds: Vec<BoundingSpaceN<T, D>>,
+        items: Vec<Item>,
+        order: &[usize],
+        nodes: Vec<BvhNode<T, D>>,
+    ) -> Self {
+        let mut slots: Vec<_> = bounds.into_iter().zip(items).map(Some).collect();
+        let (bounds, items) = order
+            .iter()
+            .map(|&i| slots[i].take().expect("item placed twice"))
+            .unzip();
+
+        Self {
+            nodes,
+            bounds,
+            items,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Bounds of all items, empty for an empty hierarchy.
+    pub fn bound(&self) -> BoundingSpaceN<T, D> {
+        self.nodes
+            .first()
+            .map(|node| node.bound.to_owned())
+            .unwrap_or_else(BoundingSpaceN::empty)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&BoundingSpaceN<T, D>, &Item)> {
+        self.bounds.iter().zip(&self.items)
+    }
+
+    /// Calls `visit` with every item whose node bounds pass `test`.
+    fn traverse<'s>(
+        &'s self,
+        mut test: impl FnMut(&BoundingSpaceN<T, D>) -> bool,
+        mut visit: impl FnMut(&'s Item),
+    ) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !test(&node.bound) {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(node.offset);
+                stack.push(index + 1);
+                continue;
+            }
+
+            for i in node.offset..node.offset + node.count {
+                if test(&self.bounds[i]) {
+                    visit(&self.items[i]);
+                }
+            }
+        }
+    }
+
+    pub fn query_point(&self, point: &Point<T, D>) -> Vec<&Item> {
+        let mut found = Vec::new();
+        self.traverse(|bound| bound.contains(point), |item| found.push(item));
+        found
+    }
+
+    pub fn query_overlap(&self, bound: &BoundingSpaceN<T, D>) -> Vec<&Item> {
+        let mut found = Vec::new();
+        self.traverse(|b| b.intersects(bound), |item| found.push(item));
+        found
+    }
+
+    /// Items whose bounds are hit by the ray and for which `intersect`
+    /// reports a hit parameter within `[t_min, t_max]`.
+    pub fn ray_all_hits<F>(
+        &self,
+        ray: &Ray<T, D>,
+        t_min: T,
+        t_max: T,
+        mut intersect: F,
+    ) -> Vec<(&Item, T)>
+    where
+        F: FnMut(&Item) -> Option<T>,
+    {
+        let ray = ray.precompute();
+        let mut hits = Vec::new();
+
+        self.traverse(
+            |bound| {
+                bound
+                    .precomputed_ray_intersection(&ray, t_min.to_owned(), t_max.to_owned())
+                    .is_some()
+            },
+            |item| {
+                if let Some(t) = intersect(item) {
+                    if t >= t_min && t <= t_max {
+                        hits.push((item, t));
+                    }
+                }
+            },
+        );
+
+        hits
+    }
+
+    /// Closest hit with a parameter within `[t_min, t_max]`, visiting nodes
+    /// front to back and skipping those entered beyond the best hit found so
+    /// far.
+    pub fn ray_first_hit<F>(
+        &self,
+        ray: &Ray<T, D>,
+        t_min: T,
+        t_max: T,
+        mut intersect: F,
+    ) -> Option<(&Item, T)>
+    where
+        F: FnMut(&Item) -> Option<T>,
+    {
+        let ray: PrecomputedRay<T, D> = ray.precompute();
+        let mut best: Option<(&Item, T)> = None;
+        let root = self.nodes.first()?.bound.precomputed_ray_intersection(
+            &ray,
+            t_min.to_owned(),
+            t_max.to_owned(),
+        )?;
+        let mut stack = vec![(0, root.0)];
+
+        while let Some((index, enter)) = stack.pop() {
+            let limit = best
+                .as_ref()
+                .map_or(t_max.to_owned(), |(_, t)| t.to_owned());
+            if enter > limit {
+                continue;
+            }
+
+            let node = &self.nodes[index];
+            if node.count == 0 {
+                let hit = |child: usize| {
+                    self.nodes[child]
+                        .bound
+                        .precomputed_ray_intersection(&ray, t_min.to_owned(), limit.to_owned())
+                        .map(|(enter, _)| (child, enter))
+                };
+
+                match (hit(index + 1), hit(node.offset)) {
+                    (Some(a), Some(b)) if a.1 < b.1 => stack.extend([b, a]),
+                    (Some(a), Some(b)) => stack.extend([a, b]),
+                    (Some(a), None) | (None, Some(a)) => stack.push(a),
+                    (None, None) => {}
+                }
+                continue;
+            }
+
+            for i in node.offset..node.offset + node.count {
+                if let Some(t) = intersect(&self.items[i]) {
+                    // Closed at `t_max` like `ray_all_hits`; ties with the
+                    // best hit keep the one found first.
+                    let closer = best.as_ref().map_or(t <= t_max, |(_, best)| t < *best);
+                    if t >= t_min && closer {
+                        best = Some((&self.items[i], t));
+                    }
+                }
+            }
+        }
+
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3, Vector3};
+
+    use super::*;
+
+    fn grid(n: usize) -> Vec<(BoundingSpaceN<f64, 3>, usize)> {
+        (0..n * n * n)
+            .map(|i| {
+                let lower = Point3::new((i % n) as f64, (i / n % n) as f64, (i / n / n) as f64);
+                let upper = lower + Vector3::repeat(0.5);
+                (BoundingSpaceN::new(lower, upper), i)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn point_and_overlap_queries() {
+        let entries = grid(6);
+        let bvh = Bvh::build(entries.clone());
+
+        assert_eq!(bvh.len(), entries.len());
+        assert_relative_eq!(bvh.bound().upper, Point3::new(5.5, 5.5, 5.5));
+        assert_eq!(
+            bvh.query_point(&Point3::new(2.25, 1.25, 3.25)),
+            vec![&(2 + 6 + 108)]
+        );
+        assert!(bvh.query_point(&Point3::new(2.75, 1.25, 3.25)).is_empty());
+
+        let window =
+            BoundingSpaceN::new(Point3::new(0.75, 0.75, 0.75), Point3::new(2.25, 2.25, 2.25));
+        let mut found: Vec<usize> = bvh.query_overlap(&window).into_iter().cloned().collect();
+        let mut expected: Vec<usize> = entries
+            .iter()
+            .filter(|(b, _)| b.intersects(&window))
+            .map(|(_, i)| *i)
+            .collect();
+        found.sort();
+        expected.sort();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn ray_queries() {
+        let entries = grid(4);
+        let bvh = Bvh::build_with(
+            entries.clone(),
+            &BvhConfig {
+                max_leaf_size: 1,
+                bins: 4,
+            },
+        );
+        let ray = Ray::new(Point3::new(-1.0, 0.25, 0.25), Vector3::new(1.0, 0.0, 0.0));
+        let intersect = |i: &usize| {
+            entries[*i]
+                .0
+                .ray_intersection(&ray, 0.0, f64::MAX)
+                .map(|t| t.0)
+        };
+
+        let (item, t) = bvh.ray_first_hit(&ray, 0.0, f64::MAX, intersect).unwrap();
+        assert_eq!(*item, 0);
+        assert_relative_eq!(t, 1.0);
+
+        let (item, _) = bvh.ray_first_hit(&ray, 1.5, f64::MAX, intersect).unwrap();
+        assert_eq!(*item, 1);
+
+        // A hit exactly at `t_max` counts for both queries.
+        assert_eq!(
+            bvh.ray_first_hit(&ray, 0.0, 1.0, intersect)
+                .map(|(i, _)| *i),
+            Some(0)
+        );
+        assert_eq!(bvh.ray_all_hits(&ray, 0.0, 1.0, intersect).len(), 1);
+
+        let mut hits: Vec<usize> = bvh
+            .ray_all_hits(&ray, 0.0, f64::MAX, intersect)
+            .into_iter()
+            .map(|(i, _)| *i)
+            .collect();
+        hits.sort();
+        assert_eq!(hits, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn coincident_centroids_use_median_split() {
+        let entries: Vec<_> = (0..10)
+            .map(|i| (BoundingSpaceN::from_point(Point2::new(1.0, 1.0)), i))
+            .collect();
+        let bvh = Bvh::build_with(
+            entries,
+            &BvhConfig {
+                max_leaf_size: 2,
+                bins: 8,
+            },
+        );
+
+        assert_eq!(bvh.query_point(&Point2::new(1.0, 1.0)).len(), 10);
+        assert!(Bvh::<f64, 2, ()>::build(Vec::new())
+            .query_point(&Point2::origin())
+            .is_empty());
+    }
+
+    #[test]
+    fn linear_build() {
+        let entries = grid(6);
+        let bvh = Bvh::build_linear(entries.clone(), &BvhConfig::default());
+        assert_eq!(bvh.len(), entries.len());
+
+        let window = BoundingSpaceN::new(Point3::new(1.2, 0.0, 2.2), Point3::new(3.1, 1.4, 4.0));
+        let mut found: Vec<usize> = bvh.query_overlap(&window).into_iter().copied().collect();
+        found.sort();
+        let expected: Vec<usize> = entries
+            .iter()
+            .filter(|(b, _)| b.intersects(&window))
+            .map(|(_, i)| *i)
+            .collect();
+        assert_eq!(found, expected);
+
+        let same: Vec<_> = (0..9)
+            .map(|i| (BoundingSpaceN::from_point(Point2::new(2.0, 3.0)), i))
+            .collect();
+        let bvh = Bvh::build_linear(same, &BvhConfig::default());
+        assert_eq!(bvh.query_point(&Point2::new(2.0, 3.0)).len(), 9);
+    }
+}
@@ -0,0 +1,324 @@
+use core::borrow::Borrow;
+
+use nalgebra::{Point, RealField, SVector};
+
+use crate::linalg::{covariance, symmetric_eigen};
+use crate::{total_cmp, BoundingSpaceN, BoundingSphereN};
+
+/// Points within `radius` of the segment from `start` to `end`, which is
+/// also the volume swept by a sphere moving along the segment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingCapsuleN<T: RealField, const D: usize> {
+    pub start: Point<T, D>,
+    pub end: Point<T, D>,
+    pub radius: T,
+}
+
+pub type BoundingCapsule2<T> = BoundingCapsuleN<T, 2>;
+pub type BoundingCapsule3<T> = BoundingCapsuleN<T, 3>;
+
+fn clamp_unit<T: RealField>(value: T) -> T {
+    value.max(T::zero()).min(T::one())
+}
+
+impl<T: RealField, const D: usize> BoundingCapsuleN<T, D> {
+    pub fn new(start: Point<T, D>, end: Point<T, D>, radius: T) -> Self {
+        Self { start, end, radius }
+    }
+
+    /// Volume swept by `sphere` when its center moves by `displacement`.
+    pub fn from_swept_sphere(sphere: &BoundingSphereN<T, D>, displacement: &SVector<T, D>) -> Self {
+        Self::new(
+            sphere.center.to_owned(),
+            &sphere.center + displacement,
+            sphere.radius.to_owned(),
+        )
+    }
+
+    /// Capsule along the principal axis of the points: the radius is the
+    /// largest distance from that axis and the segment is as short as it can
+    /// be while keeping every point inside.
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Point<T, D>>,
+    {
+        let points: Vec<Point<T, D>> = points.into_iter().map(|p| p.borrow().to_owned()).collect();
+        if points.is_empty() {
+            return None;
+        }
+
+        let (mean, covariance) = covariance(&points);
+        let (_, axes) = symmetric_eigen(&covariance);
+        let axis = if D == 0 {
+            SVector::zeros()
+        } else {
+            axes.column(0).into_owned()
+        };
+
+        let projected: Vec<(T, T)> = points
+            .iter()
+            .map(|p| {
+                let offset = p - &mean;
+                let along = axis.dot(&offset);
+                let across = (offset - &axis * along.to_owned()).norm();
+                (along, across)
+            })
+            .collect();
+        let radius = projected
+            .iter()
+            .fold(T::zero(), |acc, (_, across)| acc.max(across.to_owned()));
+
+        // Each point bounds how far in the caps may start: it must lie within
+        // the radius of the segment end beyond it.
+        let (mut lower, mut upper) = (crate::max_value::<T>(), -crate::max_value::<T>());
+        for (along, across) in projected {
+            let cap = (radius.to_owned() * radius.to_owned() - across.to_owned() * across)
+                .max(T::zero())
+                .sqrt();
+            lower = lower.min(along.to_owned() + cap.to_owned());
+            upper = upper.max(along - cap);
+        }
+        if lower > upper {
+            let middle = (lower + upper) / (T::one() + T::one());
+            lower = middle.to_owned();
+            upper = middle;
+        }
+
+        Some(Self::new(
+            &mean + &axis * lower,
+            &mean + &axis * upper,
+            radius,
+        ))
+    }
+
+    pub fn length(&self) -> T {
+        (&self.end - &self.start).norm()
+    }
+
+    /// Closest point of the segment to `point`.
+    pub fn closest_segment_point(&self, point: &Point<T, D>) -> Point<T, D> {
+        let direction = &self.end - &self.start;
+        let length_squared = direction.norm_squared();
+        if length_squared.is_zero() {
+            return self.start.to_owned();
+        }
+
+        let t = clamp_unit(direction.dot(&(point - &self.start)) / length_squared);
+        &self.start + direction * t
+    }
+
+    /// Euclidean distance to the surface, negative when `point` is inside.
+    pub fn signed_distance(&self, point: &Point<T, D>) -> T {
+        (point - self.closest_segment_point(point)).norm() - self.radius.to_owned()
+    }
+
+    /// Distance to the capsule, zero when `point` is inside.
+    pub fn distance_to_point(&self, point: &Point<T, D>) -> T {
+        self.signed_distance(point).max(T::zero())
+    }
+
+    pub fn contains(&self, point: &Point<T, D>) -> bool {
+        self.signed_distance(point) <= T::zero()
+    }
+
+    /// Closest points of the two segments (Ericson, Real-Time Collision
+    /// Detection, 5.1.9).
+    fn closest_segment_points(&self, other: &Self) -> (Point<T, D>, Point<T, D>) {
+        let (d1, d2) = (&self.end - &self.start, &other.end - &other.start);
+        let r = &self.start - &other.start;
+        let (a, e, f) = (d1.norm_squared(), d2.norm_squared(), d2.dot(&r));
+
+        let (s, t) = if a.is_zero() && e.is_zero() {
+            (T::zero(), T::zero())
+        } else if a.is_zero() {
+            (T::zero(), clamp_unit(f / e))
+        } else {
+            let c = d1.dot(&r);
+            if e.is_zero() {
+                (clamp_unit(-c / a), T::zero())
+            } else {
+                let b = d1.dot(&d2);
+                let denominator = a.to_owned() * e.to_owned() - b.to_owned() * b.to_owned();
+                let s = if denominator.is_zero() {
+                    T::zero()
+                } else {
+                    clamp_unit(
+                        (b.to_owned() * f.to_owned() - c.to_owned() * e.to_owned()) / denominator,
+                    )
+                };
+
+                let t = (b.to_owned() * s.to_owned() + f) / e;
+                if t < T::zero() {
+                    (clamp_unit(-c / a), T::zero())
+                } else if t > T::one() {
+                    (clamp_unit((b - c) / a), T::one())
+                } else {
+                    (s, t)
+                }
+            }
+        };
+
+        (&self.start + d1 * s, &other.start + d2 * t)
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        let (a, b) = self.closest_segment_points(other);
+        (b - a).norm() <= self.radius.to_owned() + other.radius.to_owned()
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphereN<T, D>) -> bool {
+        self.signed_distance(&sphere.center) <= sphere.radius
+    }
+
+    /// Squared distance between the segment and the space, minimised exactly
+    /// over the pieces where the segment stays on one side of every slab.
+    fn segment_distance_squared(&self, space: &BoundingSpaceN<T, D>) -> T {
+        let direction = &self.end - &self.start;
+        let two = T::one() + T::one();
+
+        let mut breaks = vec![T::zero(), T::one()];
+        for i in 0..D {
+            if direction[i].is_zero() {
+                continue;
+            }
+            for plane in [&space.lower[i], &space.upper[i]] {
+                let t = (plane.to_owned() - self.start[i].to_owned()) / direction[i].to_owned();
+                if t > T::zero() && t < T::one() {
+                    breaks.push(t);
+                }
+            }
+        }
+        breaks.sort_by(total_cmp);
+
+        let at = |t: T| space.distance_squared_to_point(&(&self.start + &direction * t));
+        let mut best = at(T::zero()).min(at(T::one()));
+
+        for piece in breaks.windows(2) {
+            let (from, to) = (piece[0].to_owned(), piece[1].to_owned());
+            let middle =
+                &self.start + &direction * ((from.to_owned() + to.to_owned()) / two.to_owned());
+
+            // Within a piece the distance is a quadratic in the axes where
+            // the segment lies outside the slab.
+            let (mut slope, mut curvature) = (T::zero(), T::zero());
+            for i in 0..D {
+                let bound = if middle[i] < space.lower[i] {
+                    &space.lower[i]
+                } else if middle[i] > space.upper[i] {
+                    &space.upper[i]
+                } else {
+                    continue;
+                };
+                slope += direction[i].to_owned() * (self.start[i].to_owned() - bound.to_owned());
+                curvature += direction[i].to_owned() * direction[i].to_owned();
+            }
+
+            if !curvature.is_zero() {
+                let t = (-slope / curvature).max(from).min(to);
+                best = best.min(at(t));
+            }
+        }
+
+        best
+    }
+
+    pub fn intersects_space(&self, space: &BoundingSpaceN<T, D>) -> bool {
+        !space.is_empty()
+            && self.segment_distance_squared(space)
+                <= self.radius.to_owned() * self.radius.to_owned()
+    }
+
+    /// Axis-aligned space enclosing the capsule.
+    pub fn bounding_space(&self) -> BoundingSpaceN<T, D> {
+        let offset = SVector::<T, D>::repeat(self.radius.to_owned());
+        let mut bound = BoundingSpaceN::from_point(self.start.to_owned());
+        bound.expand(&self.end);
+        BoundingSpaceN::new(&bound.lower - &offset, &bound.upper + offset)
+    }
+}
+
+impl<T: RealField, const D: usize> From<BoundingCapsuleN<T, D>> for BoundingSpaceN<T, D> {
+    fn from(capsule: BoundingCapsuleN<T, D>) -> Self {
+        capsule.bounding_space()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3, Rotation3, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn fitting_and_distance() {
+        let rotation = Rotation3::from_euler_angles(0.2, 0.5, -0.3);
+        let points: Vec<_> = (0..80)
+            .map(|i| {
+                let t = i as f64;
+                let local = Vector3::new(
+                    t / 79.0 * 10.0 - 5.0,
+                    (t * 1.7).sin() * 0.5,
+                    (t * 2.3).cos() * 0.5,
+                );
+                Point3::from(rotation * local)
+            })
+            .collect();
+
+        let capsule = BoundingCapsule3::from_points(&points).unwrap();
+        assert!(points.iter().all(|p| capsule.signed_distance(p) <= 1e-9));
+        assert!(capsule.radius < 0.75);
+        let axis = (capsule.end - capsule.start).normalize();
+        assert!(axis.dot(&(rotation * Vector3::x())).abs() > 0.99);
+
+        let flat = BoundingCapsule2::new(Point2::new(0.0, 0.0), Point2::new(4.0, 0.0), 1.0);
+        assert_relative_eq!(flat.distance_to_point(&Point2::new(2.0, 3.0)), 2.0);
+        assert_relative_eq!(flat.distance_to_point(&Point2::new(6.0, 0.0)), 1.0);
+        assert_relative_eq!(flat.signed_distance(&Point2::new(1.0, 0.5)), -0.5);
+        assert_relative_eq!(flat.distance_to_point(&Point2::new(1.0, 0.5)), 0.0);
+
+        let single = BoundingCapsule2::from_points([Point2::new(1.0, 2.0)]).unwrap();
+        assert_relative_eq!(single.radius, 0.0);
+        assert_relative_eq!(single.length(), 0.0);
+    }
+
+    #[test]
+    fn capsule_overlaps() {
+        let a = BoundingCapsule3::new(Point3::new(-2.0, 0.0, 0.0), Point3::new(2.0, 0.0, 0.0), 0.5);
+        let crossing =
+            BoundingCapsule3::new(Point3::new(0.0, -2.0, 0.9), Point3::new(0.0, 2.0, 0.9), 0.5);
+        let above =
+            BoundingCapsule3::new(Point3::new(0.0, -2.0, 1.1), Point3::new(0.0, 2.0, 1.1), 0.5);
+        assert!(a.intersects(&crossing) && crossing.intersects(&a));
+        assert!(!a.intersects(&above));
+
+        let parallel =
+            BoundingCapsule3::new(Point3::new(3.0, 0.9, 0.0), Point3::new(5.0, 0.9, 0.0), 0.5);
+        assert!(!a.intersects(&parallel));
+        let swept = BoundingCapsule3::from_swept_sphere(
+            &BoundingSphereN::new(Point3::new(3.0, 0.0, 0.0), 0.5),
+            &Vector3::new(2.0, 0.0, 0.0),
+        );
+        assert!(a.intersects(&swept));
+        assert!(a.intersects_sphere(&BoundingSphereN::new(Point3::new(0.0, 0.0, 0.9), 0.4)));
+    }
+
+    #[test]
+    fn space_overlap() {
+        let capsule = BoundingCapsule2::new(Point2::new(0.0, 3.0), Point2::new(3.0, 0.0), 0.5);
+        let corner = BoundingSpaceN::new(Point2::new(-1.0, -1.0), Point2::new(1.0, 1.0));
+        assert!(capsule.bounding_space().intersects(&corner));
+        assert!(!capsule.intersects_space(&corner));
+
+        let closer = BoundingSpaceN::new(Point2::new(-1.0, -1.0), Point2::new(1.2, 1.2));
+        assert!(capsule.intersects_space(&closer));
+        let crossed = BoundingSpaceN::new(Point2::new(1.0, -5.0), Point2::new(2.0, 5.0));
+        assert!(capsule.intersects_space(&crossed));
+        assert!(!capsule.intersects_space(&BoundingSpaceN::empty()));
+
+        let bound: BoundingSpaceN<f64, 2> = capsule.into();
+        assert_relative_eq!(bound.lower, Point2::new(-0.5, -0.5));
+        assert_relative_eq!(bound.upper, Point2::new(3.5, 3.5));
+    }
+}
@@ -0,0 +1,611 @@
+//! Convex hulls of planar and spatial point sets.
+//!
+//! Planar hulls use Andrew's monotone chain and spatial hulls Quickhull.
+//! Both keep only the extreme vertices: points on the boundary that are not
+//! corners of the hull are dropped.
+
+use core::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+
+use nalgebra::{Point2, Point3, RealField, SMatrix, Vector2, Vector3};
+
+use crate::{total_cmp, BoundingSpaceN, OrientedBoundingSpaceN};
+
+fn cross<T: RealField>(o: &Point2<T>, a: &Point2<T>, b: &Point2<T>) -> T {
+    (a.x.to_owned() - o.x.to_owned()) * (b.y.to_owned() - o.y.to_owned())
+        - (a.y.to_owned() - o.y.to_owned()) * (b.x.to_owned() - o.x.to_owned())
+}
+
+/// Counter-clockwise convex hull by Andrew's monotone chain, collinear
+/// points dropped.
+pub(crate) fn hull_2d<T: RealField>(mut points: Vec<Point2<T>>) -> Vec<Point2<T>> {
+    points.sort_by(|a, b| total_cmp(&a.x, &b.x).then_with(|| total_cmp(&a.y, &b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    // Lower chain left to right, then upper chain back, never popping
+    // below `floor` so the lower chain stays intact.
+    let push = |hull: &mut Vec<Point2<T>>, p: &Point2<T>, floor: usize| {
+        while hull.len() >= floor + 2
+            && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], p) <= T::zero()
+        {
+            hull.pop();
+        }
+        hull.push(p.to_owned());
+    };
+
+    let mut hull = Vec::with_capacity(points.len() + 1);
+    for p in &points {
+        push(&mut hull, p, 0);
+    }
+    let floor = hull.len() - 1;
+    for p in points.iter().rev().skip(1) {
+        push(&mut hull, p, floor);
+    }
+    hull.pop();
+
+    hull
+}
+
+/// Direction of the minimum-area rectangle around a counter-clockwise hull
+/// and its area, found by rotating calipers over the hull edges.
+pub(crate) fn min_area_direction<T: RealField>(hull: &[Point2<T>]) -> Option<(Vector2<T>, T)> {
+    let n = hull.len();
+    if n < 3 {
+        let direction = match hull {
+            [a, b] => (b - a).normalize(),
+            [_] => Vector2::x(),
+            _ => return None,
+        };
+        return Some((direction, T::zero()));
+    }
+
+    let next = |i: usize| (i + 1) % n;
+    let (mut right, mut top, mut left) = (1, 1, 1);
+    let mut best: Option<(Vector2<T>, T)> = None;
+
+    for i in 0..n {
+        let u = (&hull[next(i)] - &hull[i]).normalize();
+        let v = Vector2::new(-u.y.to_owned(), u.x.to_owned());
+        let along = |p: &Point2<T>| u.dot(&p.coords);
+        let across = |p: &Point2<T>| v.dot(&p.coords);
+
+        if i == 0 {
+            right = next(i);
+        }
+        for _ in 0..n {
+            if along(&hull[next(right)]) < along(&hull[right]) {
+                break;
+            }
+            right = next(right);
+        }
+        if i == 0 {
+            top = right;
+        }
+        for _ in 0..n {
+            if across(&hull[next(top)]) < across(&hull[top]) {
+                break;
+            }
+            top = next(top);
+        }
+        if i == 0 {
+            left = top;
+        }
+        for _ in 0..n {
+            if along(&hull[next(left)]) > along(&hull[left]) {
+                break;
+            }
+            left = next(left);
+        }
+
+        let width = along(&hull[right]) - along(&hull[left]);
+        let height = across(&hull[top]) - across(&hull[i]);
+        let area = width * height;
+        if best.as_ref().is_none_or(|(_, b)| area < *b) {
+            best = Some((u, area));
+        }
+    }
+
+    best
+}
+
+/// Distance below which points count as lying on a plane or line, relative
+/// to the size of the point set.
+fn tolerance<T: RealField, const D: usize>(bound: &BoundingSpaceN<T, D>) -> T {
+    bound.diagonal().norm() * T::default_epsilon() * nalgebra::convert(1024.0)
+}
+
+/// Convex polygon with counter-clockwise vertices. The hull of collinear
+/// points keeps the two end points and that of a single point just it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexHull2<T: RealField> {
+    pub vertices: Vec<Point2<T>>,
+}
+
+/// Convex polyhedron whose triangular faces index `vertices`
+/// counter-clockwise as seen from outside.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexHull3<T: RealField> {
+    pub vertices: Vec<Point3<T>>,
+    pub faces: Vec<[usize; 3]>,
+}
+
+pub fn convex_hull_2d<T, I>(points: I) -> ConvexHull2<T>
+where
+    T: RealField,
+    I: IntoIterator,
+    I::Item: Borrow<Point2<T>>,
+{
+    let points = points.into_iter().map(|p| p.borrow().to_owned()).collect();
+    ConvexHull2 {
+        vertices: hull_2d(points),
+    }
+}
+
+impl<T: RealField> ConvexHull2<T> {
+    pub fn bounding_space(&self) -> BoundingSpaceN<T, 2> {
+        self.vertices.iter().collect()
+    }
+
+    pub fn area(&self) -> T {
+        let n = self.vertices.len();
+        let twice = (0..n).fold(T::zero(), |acc, i| {
+            let (a, b) = (&self.vertices[i], &self.vertices[(i + 1) % n]);
+            acc + a.x.to_owned() * b.y.to_owned() - b.x.to_owned() * a.y.to_owned()
+        });
+        twice / (T::one() + T::one())
+    }
+
+    /// Whether `point` is inside the hull or on its boundary.
+    pub fn contains(&self, point: &Point2<T>) -> bool {
+        let vertices = &self.vertices;
+        let bound = self.bounding_space();
+        let tolerance = tolerance(&bound);
+
+        match vertices.len() {
+            0 => false,
+            1 => vertices[0] == *point,
+            2 => {
+                let length = (&vertices[1] - &vertices[0]).norm();
+                cross(&vertices[0], &vertices[1], point).abs() <= tolerance * length
+                    && bound.contains(point)
+            }
+            n => (0..n).all(|i| {
+                let (a, b) = (&vertices[i], &vertices[(i + 1) % n]);
+                cross(a, b, point) >= -(tolerance.to_owned() * (b - a).norm())
+            }),
+        }
+    }
+
+    /// Smallest-area rectangle around the hull; one of its sides always lies
+    /// along a hull edge.
+    pub fn minimum_area_rectangle(&self) -> Option<OrientedBoundingSpaceN<T, 2>> {
+        let (u, _) = min_area_direction(&self.vertices)?;
+        let rotation = SMatrix::<T, 2, 2>::new(
+            u.x.to_owned(),
+            -u.y.to_owned(),
+            u.y.to_owned(),
+            u.x.to_owned(),
+        );
+        OrientedBoundingSpaceN::fit_with_axes(&self.vertices, rotation)
+    }
+}
+
+struct Face<T: RealField> {
+    vertices: [usize; 3],
+    normal: Vector3<T>,
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl<T: RealField> Face<T> {
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+struct Quickhull<'a, T: RealField> {
+    points: &'a [Point3<T>],
+    faces: Vec<Face<T>>,
+    /// Face owning each directed edge.
+    edges: HashMap<(usize, usize), usize>,
+    /// Faces that received outside points since they were last processed.
+    pending: Vec<usize>,
+    tolerance: T,
+}
+
+impl<T: RealField> Quickhull<'_, T> {
+    fn distance(&self, face: usize, point: usize) -> T {
+        let face = &self.faces[face];
+        face.normal
+            .dot(&(&self.points[point] - &self.points[face.vertices[0]]))
+    }
+
+    fn add_face(&mut self, vertices: [usize; 3]) -> usize {
+        let [a, b, c] = vertices.map(|v| &self.points[v]);
+        let normal = (b - a).cross(&(c - a)).normalize();
+        let index = self.faces.len();
+
+        self.faces.push(Face {
+            vertices,
+            normal,
+            outside: Vec::new(),
+            alive: true,
+        });
+        for edge in self.faces[index].edges() {
+            self.edges.insert(edge, index);
+        }
+
+        index
+    }
+
+    fn remove_face(&mut self, face: usize) {
+        self.faces[face].alive = false;
+        for edge in self.faces[face].edges() {
+            self.edges.remove(&edge);
+        }
+    }
+
+    /// Adds `point` to the outside set of the first of `faces` it lies above.
+    fn assign(&mut self, point: usize, faces: impl IntoIterator<Item = usize>) -> bool {
+        for face in faces {
+            if self.faces[face].alive && self.distance(face, point) > self.tolerance {
+                if self.faces[face].outside.is_empty() {
+                    self.pending.push(face);
+                }
+                self.faces[face].outside.push(point);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn farthest(&self, face: usize) -> usize {
+        *self.faces[face]
+            .outside
+            .iter()
+            .max_by(|&&a, &&b| total_cmp(&self.distance(face, a), &self.distance(face, b)))
+            .expect("face with outside points")
+    }
+
+    fn run(&mut self) {
+        while let Some(start) = self.pending.pop() {
+            if !self.faces[start].alive || self.faces[start].outside.is_empty() {
+                continue;
+            }
+            let eye = self.farthest(start);
+
+            // Faces seen from the eye form a connected patch around `start`.
+            // A missing twin, possible when tolerances disagree on nearly
+            // coplanar input, is treated as a horizon edge.
+            let mut visible = vec![start];
+            let mut is_visible = HashSet::from([start]);
+            let mut stack = vec![start];
+            while let Some(face) = stack.pop() {
+                for (a, b) in self.faces[face].edges() {
+                    let Some(&twin) = self.edges.get(&(b, a)) else {
+                        continue;
+                    };
+                    if !is_visible.contains(&twin) && self.distance(twin, eye) > self.tolerance {
+                        is_visible.insert(twin);
+                        visible.push(twin);
+                        stack.push(twin);
+                    }
+                }
+            }
+
+            let horizon: Vec<(usize, usize)> = visible
+                .iter()
+                .flat_map(|&face| self.faces[face].edges())
+                .filter(|&(a, b)| {
+                    self.edges
+                        .get(&(b, a))
+                        .is_none_or(|twin| !is_visible.contains(twin))
+                })
+                .collect();
+            let orphans: Vec<usize> = visible
+                .iter()
+                .flat_map(|&face| core::mem::take(&mut self.faces[face].outside))
+                .collect();
+            for &face in &visible {
+                self.remove_face(face);
+            }
+
+            // Faces across the horizon are the only surviving faces an orphan
+            // can still lie above once tolerances disagree.
+            let neighbors: Vec<usize> = horizon
+                .iter()
+                .filter_map(|&(a, b)| self.edges.get(&(b, a)).copied())
+                .collect();
+            let created: Vec<usize> = horizon
+                .into_iter()
+                .map(|(a, b)| self.add_face([a, b, eye]))
+                .collect();
+            for point in orphans.into_iter().filter(|&p| p != eye) {
+                if !self.assign(point, created.iter().copied()) {
+                    self.assign(point, neighbors.iter().copied());
+                }
+            }
+        }
+    }
+}
+
+/// Indices of four points spanning a tetrahedron, `None` when all points
+/// are coplanar.
+fn initial_simplex<T: RealField>(points: &[Point3<T>], tolerance: &T) -> Option<[usize; 4]> {
+    let extreme = |axis: usize, upper: bool| {
+        (0..points.len()).fold(0, |best, i| {
+            let beyond = if upper {
+                points[i][axis] > points[best][axis]
+            } else {
+                points[i][axis] < points[best][axis]
+            };
+            if beyond {
+                i
+            } else {
+                best
+            }
+        })
+    };
+    let extremes: Vec<usize> = (0..3)
+        .flat_map(|axis| [extreme(axis, false), extreme(axis, true)])
+        .collect();
+
+    let farthest = |measure: &dyn Fn(&Point3<T>) -> T| {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, measure(p)))
+            .fold(
+                (0, T::zero()),
+                |best, (i, m)| if m > best.1 { (i, m) } else { best },
+            )
+    };
+
+    let (i0, i1) = extremes
+        .iter()
+        .flat_map(|&a| extremes.iter().map(move |&b| (a, b)))
+        .fold((0, 0), |best, (a, b)| {
+            let span = |(a, b): (usize, usize)| (&points[b] - &points[a]).norm_squared();
+            if span((a, b)) > span(best) {
+                (a, b)
+            } else {
+                best
+            }
+        });
+    let (a, b) = (&points[i0], &points[i1]);
+    if (b - a).norm() <= *tolerance {
+        return None;
+    }
+
+    let direction = (b - a).normalize();
+    let (i2, off_line) = farthest(&|p| (p - a).cross(&direction).norm());
+    if off_line <= *tolerance {
+        return None;
+    }
+
+    let normal = (b - a).cross(&(&points[i2] - a)).normalize();
+    let (i3, off_plane) = farthest(&|p| normal.dot(&(p - a)).abs());
+    if off_plane <= *tolerance {
+        return None;
+    }
+
+    Some([i0, i1, i2, i3])
+}
+
+/// Quickhull, `None` when the points do not span a volume.
+pub fn convex_hull_3d<T, I>(points: I) -> Option<ConvexHull3<T>>
+where
+    T: RealField,
+    I: IntoIterator,
+    I::Item: Borrow<Point3<T>>,
+{
+    let points: Vec<Point3<T>> = points.into_iter().map(|p| p.borrow().to_owned()).collect();
+    let tolerance = tolerance(&BoundingSpaceN::from_points(&points)?);
+    let simplex = initial_simplex(&points, &tolerance)?;
+
+    let mut hull = Quickhull {
+        points: &points,
+        faces: Vec::new(),
+        edges: HashMap::new(),
+        pending: Vec::new(),
+        tolerance,
+    };
+
+    let centroid = simplex
+        .iter()
+        .fold(Vector3::zeros(), |acc, &i| acc + &points[i].coords)
+        / nalgebra::convert::<f64, T>(4.0);
+    for [a, b, c] in [[0, 1, 2], [0, 3, 1], [1, 3, 2], [2, 3, 0]] {
+        let (a, mut b, mut c) = (simplex[a], simplex[b], simplex[c]);
+        let normal = (&points[b] - &points[a]).cross(&(&points[c] - &points[a]));
+        if normal.dot(&(&centroid - &points[a].coords)) > T::zero() {
+            core::mem::swap(&mut b, &mut c);
+        }
+        hull.add_face([a, b, c]);
+    }
+
+    for point in (0..points.len()).filter(|p| !simplex.contains(p)) {
+        hull.assign(point, 0..4);
+    }
+    hull.run();
+
+    let mut remap: HashMap<usize, usize> = HashMap::new();
+    let mut vertices = Vec::new();
+    let faces = hull
+        .faces
+        .iter()
+        .filter(|f| f.alive)
+        .map(|f| {
+            f.vertices.map(|v| {
+                *remap.entry(v).or_insert_with(|| {
+                    vertices.push(points[v].to_owned());
+                    vertices.len() - 1
+                })
+            })
+        })
+        .collect();
+
+    Some(ConvexHull3 { vertices, faces })
+}
+
+impl<T: RealField> ConvexHull3<T> {
+    pub fn bounding_space(&self) -> BoundingSpaceN<T, 3> {
+        self.vertices.iter().collect()
+    }
+
+    pub fn volume(&self) -> T {
+        let six: T = nalgebra::convert(6.0);
+        self.faces.iter().fold(T::zero(), |acc, &[a, b, c]| {
+            let (a, b, c) = (&self.vertices[a], &self.vertices[b], &self.vertices[c]);
+            acc + a.coords.dot(&b.coords.cross(&c.coords))
+        }) / six
+    }
+
+    /// Whether `point` is inside the hull or on its boundary.
+    pub fn contains(&self, point: &Point3<T>) -> bool {
+        let tolerance = tolerance(&self.bounding_space());
+        self.faces.iter().all(|&[a, b, c]| {
+            let (a, b, c) = (&self.vertices[a], &self.vertices[b], &self.vertices[c]);
+            let normal = (b - a).cross(&(c - a)).normalize();
+            normal.dot(&(point - a)) <= tolerance
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::Rotation2;
+
+    use super::*;
+
+    #[test]
+    fn planar_hull() {
+        let mut points: Vec<_> = (0..5)
+            .flat_map(|i| (0..4).map(move |j| Point2::new(i as f64, j as f64)))
+            .collect();
+        points.push(Point2::new(2.0, 2.0));
+
+        let hull = convex_hull_2d(&points);
+        assert_eq!(
+            hull.vertices,
+            vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(4.0, 0.0),
+                Point2::new(4.0, 3.0),
+                Point2::new(0.0, 3.0)
+            ]
+        );
+        assert_relative_eq!(hull.area(), 12.0);
+        assert!(points.iter().all(|p| hull.contains(p)));
+        assert!(!hull.contains(&Point2::new(4.1, 1.0)));
+        assert_relative_eq!(hull.bounding_space().upper, Point2::new(4.0, 3.0));
+
+        let segment = convex_hull_2d([
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(2.0, 2.0),
+        ]);
+        assert_eq!(segment.vertices.len(), 2);
+        assert!(segment.contains(&Point2::new(0.5, 0.5)));
+        assert!(!segment.contains(&Point2::new(0.5, 0.6)));
+    }
+
+    #[test]
+    fn minimum_area_rectangle() {
+        let rotation = Rotation2::new(0.3);
+        let points: Vec<_> = [
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 1.0),
+            (0.0, 1.0),
+            (2.0, 1.0),
+            (1.0, 0.5),
+        ]
+        .iter()
+        .map(|&(x, y)| Point2::from(rotation * Vector2::new(x, y)))
+        .collect();
+
+        let hull = convex_hull_2d(&points);
+        let rectangle = hull.minimum_area_rectangle().unwrap();
+        assert_relative_eq!(rectangle.volume(), 4.0, epsilon = 1e-12);
+        assert!(hull.bounding_space().volume() > 4.5);
+        assert!(points
+            .iter()
+            .all(|p| { (rectangle.to_local(p).abs() - rectangle.half_extents).max() < 1e-12 }));
+    }
+
+    #[test]
+    fn spatial_hull() {
+        let mut points: Vec<_> =
+            BoundingSpaceN::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0))
+                .corners()
+                .collect();
+        points.extend((0..50).map(|i| {
+            let t = i as f64;
+            Point3::new((t * 0.7).sin(), (t * 1.3).cos(), (t * 0.4).sin() * 0.9)
+        }));
+        points.push(Point3::new(1.0, 0.0, 0.0));
+
+        let hull = convex_hull_3d(&points).unwrap();
+        assert_eq!(hull.vertices.len(), 8);
+        assert_eq!(hull.faces.len(), 12);
+        assert_relative_eq!(hull.volume(), 8.0, epsilon = 1e-12);
+        assert!(points.iter().all(|p| hull.contains(p)));
+        assert!(!hull.contains(&Point3::new(0.0, 0.0, 1.01)));
+
+        let sphere: Vec<_> = (0..200)
+            .map(|i| {
+                let t = i as f64 + 0.5;
+                let z = 1.0 - 2.0 * t / 200.0;
+                let r = (1.0 - z * z).sqrt();
+                let angle = t * 2.399963229728653;
+                Point3::new(r * angle.cos(), r * angle.sin(), z)
+            })
+            .collect();
+        let hull = convex_hull_3d(&sphere).unwrap();
+        assert_eq!(hull.vertices.len(), 200);
+        assert_eq!(hull.faces.len(), 2 * 200 - 4);
+        assert!(hull.volume() < 4.0 / 3.0 * core::f64::consts::PI);
+        assert!(hull.volume() > 3.9);
+        assert!(sphere.iter().all(|p| hull.contains(p)));
+
+        let flat = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ];
+        assert!(convex_hull_3d(flat).is_none());
+    }
+
+    #[test]
+    fn nearly_coplanar_hull_is_closed() {
+        for scale in (0..13).map(|k| 10f64.powf(-16.0 + k as f64 * 0.5)) {
+            let mut points: Vec<_> = (0..400)
+                .map(|i| {
+                    let (x, y) = ((i % 20) as f64 / 19.0, (i / 20) as f64 / 19.0);
+                    let noise = ((i as f64 * 12.9898).sin() * 43758.5453).fract();
+                    Point3::new(x, y, noise * scale)
+                })
+                .collect();
+            points.push(Point3::new(0.5, 0.5, 1.0));
+            points.push(Point3::new(0.3, 0.6, -1e-3));
+
+            // Every directed edge has its reverse, so no face is left open.
+            let hull = convex_hull_3d(&points).unwrap();
+            let edges: HashSet<(usize, usize)> = hull
+                .faces
+                .iter()
+                .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+                .collect();
+            assert_eq!(edges.len(), 3 * hull.faces.len());
+            assert!(edges.iter().all(|&(a, b)| edges.contains(&(b, a))));
+            assert_eq!(hull.faces.len(), 2 * hull.vertices.len() - 4);
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Morton (Z-order) and Hilbert keys for points inside a reference space.
+//!
+//! Points are normalised into the reference `BoundingSpaceN` and quantised to
+//! `bits` per axis, giving integer cells in `[0, 2^bits)`. Keys interleave one
+//! bit of every axis per level, so a key needs `D * bits` bits and may be a
+//! `u32`, `u64` or `u128` as long as it fits. Axis 0 is the least significant
+//! bit of each level of a Morton key.
+
+use nalgebra::{convert, try_convert, RealField};
+
+use crate::BoundingSpaceN;
+
+/// Unsigned integer usable as a curve key.
+pub trait CurveKey: Copy + Ord + Into<u128> + TryFrom<u128> {
+    const BITS: u32;
+}
+
+impl CurveKey for u32 {
+    const BITS: u32 = u32::BITS;
+}
+
+impl CurveKey for u64 {
+    const BITS: u32 = u64::BITS;
+}
+
+impl CurveKey for u128 {
+    const BITS: u32 = u128::BITS;
+}
+
+/// Cells are `u32` per axis, so the grid can be at most `2^32` wide.
+fn check_depth(bits: u32) {
+    assert!((1..=32).contains(&bits), "bits per axis must be in 1..=32");
+}
+
+fn check_bits<K: CurveKey>(dimensions: usize, bits: u32) {
+    check_depth(bits);
+    assert!(
+        dimensions as u64 * bits as u64 <= K::BITS as u64,
+        "{dimensions} axes of {bits} bits do not fit a {}-bit key",
+        K::BITS
+    );
+}
+
+/// Hilbert curves additionally need at least one axis to rotate.
+fn check_hilbert<K: CurveKey>(dimensions: usize, bits: u32) {
+    check_bits::<K>(dimensions, bits);
+    assert!(dimensions > 0, "a Hilbert curve needs at least one axis");
+}
+
+fn to_key<K: CurveKey>(value: u128) -> K {
+    K::try_from(value).unwrap_or_else(|_| unreachable!("key bits checked"))
+}
+
+/// Cell of `point` in the `2^bits` grid over `reference`. Points outside the
+/// reference are clamped to the border cells and zero-width axes map to 0.
+pub fn quantize<T: RealField, const D: usize>(
+    reference: &BoundingSpaceN<T, D>,
+    point: &nalgebra::Point<T, D>,
+    bits: u32,
+) -> [u32; D] {
+    check_depth(bits);
+    let max = (1u64 << bits) - 1;
+    core::array::from_fn(|i| {
+        let extent = reference.upper[i].to_owned() - reference.lower[i].to_owned();
+        if extent <= T::zero() {
+            return 0;
+        }
+
+        let relative = (point[i].to_owned() - reference.lower[i].to_owned()) / extent;
+        let scaled = try_convert::<T, f64>(relative).unwrap_or(0.0) * (1u64 << bits) as f64;
+        (scaled.floor().max(0.0) as u64).min(max) as u32
+    })
+}
+
+/// Region of `reference` covered by a quantised cell.
+pub fn cell_bound<T: RealField, const D: usize>(
+    reference: &BoundingSpaceN<T, D>,
+    cell: &[u32; D],
+    bits: u32,
+) -> BoundingSpaceN<T, D> {
+    check_depth(bits);
+    let cells: T = convert((1u64 << bits) as f64);
+    let mut bound = reference.to_owned();
+
+    for (i, &c) in cell.iter().enumerate() {
+        let step =
+            (reference.upper[i].to_owned() - reference.lower[i].to_owned()) / cells.to_owned();
+        let lower = reference.lower[i].to_owned() + step.to_owned() * convert(c as f64);
+        bound.upper[i] = lower.to_owned() + step;
+        bound.lower[i] = lower;
+    }
+
+    bound
+}
+
+fn interleave<const D: usize>(cell: &[u32; D], bits: u32) -> u128 {
+    let mut key = 0u128;
+    for level in 0..bits {
+        for (axis, &c) in cell.iter().enumerate() {
+            key |= (((c >> level) & 1) as u128) << (level as usize * D + axis);
+        }
+    }
+    key
+}
+
+fn deinterleave<const D: usize>(key: u128, bits: u32) -> [u32; D] {
+    let mut cell = [0u32; D];
+    for level in 0..bits {
+        for (axis, c) in cell.iter_mut().enumerate() {
+            *c |= (((key >> (level as usize * D + axis)) & 1) as u32) << level;
+        }
+    }
+    cell
+}
+
+pub fn morton_encode<K: CurveKey, const D: usize>(cell: &[u32; D], bits: u32) -> K {
+    check_bits::<K>(D, bits);
+    to_key(interleave(cell, bits))
+}
+
+pub fn morton_decode<K: CurveKey, const D: usize>(key: K, bits: u32) -> [u32; D] {
+    check_bits::<K>(D, bits);
+    deinterleave(key.into(), bits)
+}
+
+/// Hilbert index of a cell, following Skilling's transpose formulation
+/// ("Programming the Hilbert curve", 2004) so it works in any dimension.
+pub fn hilbert_encode<K: CurveKey, const D: usize>(cell: &[u32; D], bits: u32) -> K {
+    check_hilbert::<K>(D, bits);
+    let mut x = cell.map(u64::from);
+
+    // Inverse undo of the excess work.
+    let mut q = 1u64 << (bits - 1);
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..D {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+
+    // Gray encode.
+    for i in 1..D {
+        x[i] ^= x[i - 1];
+    }
+    let mut t = 0;
+    let mut q = 1u64 << (bits - 1);
+    while q > 1 {
+        if x[D - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for value in &mut x {
+        *value ^= t;
+    }
+
+    // The transposed index has axis 0 most significant at every level.
+    let mut transposed = [0u32; D];
+    for (i, value) in x.iter().enumerate() {
+        transposed[D - 1 - i] = *value as u32;
+    }
+    to_key(interleave(&transposed, bits))
+}
+
+pub fn hilbert_decode<K: CurveKey, const D: usize>(key: K, bits: u32) -> [u32; D] {
+    check_hilbert::<K>(D, bits);
+    let transposed: [u32; D] = deinterleave(key.into(), bits);
+    let mut x: [u64; D] = core::array::from_fn(|i| transposed[D - 1 - i] as u64);
+
+    // Gray decode.
+    let t = x[D - 1] >> 1;
+    for i in (1..D).rev() {
+        x[i] ^= x[i - 1];
+    }
+    x[0] ^= t;
+
+    // Undo the excess work.
+    let mut q = 2u64;
+    while q != 1u64 << bits {
+        let p = q - 1;
+        for i in (0..D).rev() {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q <<= 1;
+    }
+
+    x.map(|value| value as u32)
+}
+
+pub fn morton_key<K: CurveKey, T: RealField, const D: usize>(
+    reference: &BoundingSpaceN<T, D>,
+    point: &nalgebra::Point<T, D>,
+    bits: u32,
+) -> K {
+    morton_encode(&quantize(reference, point, bits), bits)
+}
+
+pub fn hilbert_key<K: CurveKey, T: RealField, const D: usize>(
+    reference: &BoundingSpaceN<T, D>,
+    point: &nalgebra::Point<T, D>,
+    bits: u32,
+) -> K {
+    hilbert_encode(&quantize(reference, point, bits), bits)
+}
+
+/// Region of `reference` covered by the cell of a Morton key.
+pub fn morton_cell<K: CurveKey, T: RealField, const D: usize>(
+    reference: &BoundingSpaceN<T, D>,
+    key: K,
+    bits: u32,
+) -> BoundingSpaceN<T, D> {
+    cell_bound(reference, &morton_decode(key, bits), bits)
+}
+
+/// Region of `reference` covered by the cell of a Hilbert key.
+pub fn hilbert_cell<K: CurveKey, T: RealField, const D: usize>(
+    reference: &BoundingSpaceN<T, D>,
+    key: K,
+    bits: u32,
+) -> BoundingSpaceN<T, D> {
+    cell_bound(reference, &hilbert_decode(key, bits), bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3};
+
+    use super::*;
+
+    #[test]
+    fn morton_keys() {
+        assert_eq!(morton_encode::<u32, 2>(&[1, 0], 4), 0b01);
+        assert_eq!(morton_encode::<u32, 2>(&[0, 1], 4), 0b10);
+        assert_eq!(morton_encode::<u32, 2>(&[3, 5], 4), 0b10_01_11);
+
+        let cell = [1023, 512, 7];
+        let key: u32 = morton_encode(&cell, 10);
+        assert_eq!(morton_decode::<u32, 3>(key, 10), cell);
+        let key: u128 = morton_encode(&[u32::MAX, 0, 12345], 32);
+        assert_eq!(morton_decode::<u128, 3>(key, 32), [u32::MAX, 0, 12345]);
+
+        let reference = BoundingSpaceN::new(Point3::new(0.0, 0.0, 0.0), Point3::new(8.0, 4.0, 2.0));
+        let point = Point3::new(5.5, 1.2, 1.9);
+        let key: u64 = morton_key(&reference, &point, 3);
+        let cell = morton_cell(&reference, key, 3);
+        assert!(cell.contains(&point));
+        assert_relative_eq!(cell.lower, Point3::new(5.0, 1.0, 1.75));
+        assert_relative_eq!(cell.upper, Point3::new(6.0, 1.5, 2.0));
+
+        let outside = Point3::new(-1.0, 10.0, 1.0);
+        assert_eq!(quantize(&reference, &outside, 3), [0, 7, 4]);
+    }
+
+    #[test]
+    fn hilbert_curve_is_continuous() {
+        let mut previous = hilbert_decode::<u32, 2>(0, 3);
+        assert_eq!(previous, [0, 0]);
+        for key in 1..64u32 {
+            let cell = hilbert_decode::<u32, 2>(key, 3);
+            let steps: u32 = (0..2).map(|i| cell[i].abs_diff(previous[i])).sum();
+            assert_eq!(steps, 1);
+            assert_eq!(hilbert_encode::<u32, 2>(&cell, 3), key);
+            previous = cell;
+        }
+
+        let mut previous = hilbert_decode::<u64, 3>(0, 2);
+        for key in 1..64u64 {
+            let cell = hilbert_decode::<u64, 3>(key, 2);
+            let steps: u32 = (0..3).map(|i| cell[i].abs_diff(previous[i])).sum();
+            assert_eq!(steps, 1);
+            assert_eq!(hilbert_encode::<u64, 3>(&cell, 2), key);
+            previous = cell;
+        }
+
+        let reference = BoundingSpaceN::new(Point2::new(-1.0, -1.0), Point2::new(1.0, 1.0));
+        let point = Point2::new(0.3, -0.7);
+        let key: u32 = hilbert_key(&reference, &point, 8);
+        assert!(hilbert_cell(&reference, key, 8).contains(&point));
+    }
+
+    #[test]
+    #[should_panic]
+    fn key_too_small() {
+        morton_encode::<u32, 3>(&[0, 0, 0], 11);
+    }
+
+    #[test]
+    #[should_panic(expected = "bits per axis")]
+    fn quantize_too_deep() {
+        let reference = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(1.0, 1.0));
+        quantize(&reference, &Point2::new(0.5, 0.5), 64);
+    }
+
+    #[test]
+    fn morton_without_axes() {
+        assert_eq!(morton_encode::<u32, 0>(&[], 4), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one axis")]
+    fn hilbert_without_axes() {
+        hilbert_encode::<u32, 0>(&[], 4);
+    }
+}
@@ -0,0 +1,210 @@
+use nalgebra::{Point, RealField, SVector};
+
+use crate::{max_value, BoundingSpaceN};
+
+impl<T: RealField, const D: usize> BoundingSpaceN<T, D> {
+    /// Closest point of the space to `point`, the point itself when inside.
+    pub fn clamp_point(&self, point: &Point<T, D>) -> Point<T, D> {
+        let mut clamped = point.to_owned();
+
+        for i in 0..D {
+            clamped[i] = clamped[i]
+                .to_owned()
+                .max(self.lower[i].to_owned())
+                .min(self.upper[i].to_owned());
+        }
+
+        clamped
+    }
+
+    /// Squared distance to the closest point, `T::max_value()` for an empty space.
+    pub fn distance_squared_to_point(&self, point: &Point<T, D>) -> T {
+        if self.is_empty() {
+            return max_value();
+        }
+
+        (self.clamp_point(point) - point).norm_squared()
+    }
+
+    pub fn distance_to_point(&self, point: &Point<T, D>) -> T {
+        if self.is_empty() {
+            return max_value();
+        }
+
+        (self.clamp_point(point) - point).norm()
+    }
+
+    /// Euclidean distance to the boundary, negative when `point` is inside.
+    pub fn signed_distance(&self, point: &Point<T, D>) -> T {
+        if !self.contains(point) {
+            return self.distance_to_point(point);
+        }
+
+        let depth = (0..D).fold(max_value::<T>(), |acc, i| {
+            let below = point[i].to_owned() - self.lower[i].to_owned();
+            let above = self.upper[i].to_owned() - point[i].to_owned();
+            acc.min(below).min(above)
+        });
+
+        -depth
+    }
+
+    pub fn farthest_point_from(&self, point: &Point<T, D>) -> Point<T, D> {
+        let mut farthest = self.lower.to_owned();
+
+        for i in 0..D {
+            let below = point[i].to_owned() - self.lower[i].to_owned();
+            let above = self.upper[i].to_owned() - point[i].to_owned();
+            if above > below {
+                farthest[i] = self.upper[i].to_owned();
+            }
+        }
+
+        farthest
+    }
+
+    /// Squared distance between the closest points of two spaces, zero when
+    /// they overlap.
+    pub fn min_distance_squared(&self, other: &Self) -> T {
+        if self.is_empty() || other.is_empty() {
+            return max_value();
+        }
+
+        (0..D).fold(T::zero(), |acc, i| {
+            let gap = (other.lower[i].to_owned() - self.upper[i].to_owned())
+                .max(self.lower[i].to_owned() - other.upper[i].to_owned())
+                .max(T::zero());
+            acc + gap.to_owned() * gap
+        })
+    }
+
+    /// Squared distance between the farthest points of two spaces.
+    pub fn max_distance_squared(&self, other: &Self) -> T {
+        if self.is_empty() || other.is_empty() {
+            return max_value();
+        }
+
+        (0..D).fold(T::zero(), |acc, i| {
+            let span = (other.upper[i].to_owned() - self.lower[i].to_owned())
+                .abs()
+                .max((self.upper[i].to_owned() - other.lower[i].to_owned()).abs());
+            acc + span.to_owned() * span
+        })
+    }
+
+    /// Squared MINMAXDIST of Roussopoulos et al.: an upper bound on the
+    /// distance from `point` to the nearest object whose bounds are this space.
+    pub fn min_max_distance_squared(&self, point: &Point<T, D>) -> T {
+        if self.is_empty() {
+            return max_value();
+        }
+
+        let center = self.center();
+        let mut near = SVector::<T, D>::zeros();
+        let mut far = SVector::<T, D>::zeros();
+
+        for i in 0..D {
+            let (rm, r_max) = if point[i] <= center[i] {
+                (&self.lower[i], &self.upper[i])
+            } else {
+                (&self.upper[i], &self.lower[i])
+            };
+            let rm = point[i].to_owned() - rm.to_owned();
+            let r_max = point[i].to_owned() - r_max.to_owned();
+            near[i] = rm.to_owned() * rm;
+            far[i] = r_max.to_owned() * r_max;
+        }
+
+        let far_sum = far.sum();
+        (0..D).fold(max_value::<T>(), |acc, k| {
+            acc.min(far_sum.to_owned() - far[k].to_owned() + near[k].to_owned())
+        })
+    }
+
+    /// Symmetric Hausdorff distance between the two spaces as solid sets.
+    pub fn hausdorff_distance(&self, other: &Self) -> T {
+        if self.is_empty() || other.is_empty() {
+            return max_value();
+        }
+
+        self.directed_hausdorff_squared(other)
+            .max(other.directed_hausdorff_squared(self))
+            .sqrt()
+    }
+
+    fn directed_hausdorff_squared(&self, other: &Self) -> T {
+        let axis_distance = |value: &T, i: usize| {
+            (other.lower[i].to_owned() - value.to_owned())
+                .max(value.to_owned() - other.upper[i].to_owned())
+                .max(T::zero())
+        };
+
+        (0..D).fold(T::zero(), |acc, i| {
+            let d = axis_distance(&self.lower[i], i).max(axis_distance(&self.upper[i], i));
+            acc + d.to_owned() * d
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3};
+
+    use super::*;
+
+    #[test]
+    fn point_distances() {
+        let bound = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(2.0, 1.0));
+
+        let outside = Point2::new(5.0, -3.0);
+        assert_relative_eq!(bound.clamp_point(&outside), Point2::new(2.0, 0.0));
+        assert_relative_eq!(bound.distance_squared_to_point(&outside), 18.0);
+        assert_relative_eq!(bound.distance_to_point(&outside), 18.0_f64.sqrt());
+        assert_relative_eq!(bound.signed_distance(&outside), 18.0_f64.sqrt());
+
+        let inside = Point2::new(0.5, 0.75);
+        assert_relative_eq!(bound.clamp_point(&inside), inside);
+        assert_relative_eq!(bound.distance_to_point(&inside), 0.0);
+        assert_relative_eq!(bound.signed_distance(&inside), -0.25);
+
+        assert_relative_eq!(bound.farthest_point_from(&inside), Point2::new(2.0, 0.0));
+        assert_relative_eq!(bound.farthest_point_from(&outside), Point2::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn space_distances() {
+        let a = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(1.0, 1.0));
+        let b = BoundingSpaceN::new(Point2::new(3.0, 2.0), Point2::new(4.0, 5.0));
+
+        assert_relative_eq!(a.min_distance_squared(&b), 4.0 + 1.0);
+        assert_relative_eq!(a.max_distance_squared(&b), 16.0 + 25.0);
+        assert_relative_eq!(a.min_distance_squared(&a), 0.0);
+
+        let inner = BoundingSpaceN::new(Point2::new(0.25, 0.25), Point2::new(0.5, 0.5));
+        assert_relative_eq!(a.hausdorff_distance(&inner), 0.5_f64.hypot(0.5));
+        assert_relative_eq!(a.hausdorff_distance(&a), 0.0);
+        assert_relative_eq!(a.hausdorff_distance(&b), 3.0_f64.hypot(4.0));
+    }
+
+    #[test]
+    fn min_max_distance() {
+        let bound = BoundingSpaceN::new(Point2::new(1.0, 1.0), Point2::new(3.0, 2.0));
+        let point = Point2::origin();
+
+        // Nearest face along x costs 1 + 2^2, along y costs 3^2 + 1.
+        assert_relative_eq!(bound.min_max_distance_squared(&point), 5.0);
+        assert!(bound.distance_squared_to_point(&point) <= bound.min_max_distance_squared(&point));
+        assert!(
+            bound.min_max_distance_squared(&point)
+                <= (bound.farthest_point_from(&point) - point).norm_squared()
+        );
+    }
+
+    #[test]
+    fn empty_is_infinitely_far() {
+        let empty = BoundingSpaceN::<f64, 3>::empty();
+        assert_relative_eq!(empty.distance_to_point(&Point3::origin()), f64::MAX);
+        assert!(empty.signed_distance(&Point3::origin()) > 0.0);
+    }
+}
@@ -0,0 +1,422 @@
+//! Incrementally updated AABB tree in the style of Box2D and Bullet.
+//!
+//! Leaves store bounds enlarged by a margin, so objects moving a little do
+//! not need to be reinserted. The tree is kept balanced by rotating nodes on
+//! the path from every inserted or removed leaf to the root.
+
+use nalgebra::{RealField, SVector};
+
+use crate::bvh::sah_measure;
+use crate::BoundingSpaceN;
+
+/// Handle to a proxy. Node slots are recycled, so an id must not be used
+/// after its proxy is removed: it may then refer to a later proxy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProxyId(usize);
+
+#[derive(Debug, Clone)]
+struct TreeNode<T: RealField, const D: usize, V> {
+    fat: BoundingSpaceN<T, D>,
+    parent: Option<usize>,
+    children: Option<[usize; 2]>,
+    height: usize,
+    value: Option<V>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DynamicTree<T: RealField, const D: usize, V> {
+    nodes: Vec<TreeNode<T, D, V>>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    margin: T,
+    proxies: usize,
+}
+
+impl<T: RealField, const D: usize, V> DynamicTree<T, D, V> {
+    pub fn new(margin: T) -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: None,
+            margin,
+            proxies: 0,
+        }
+    }
+
+    fn fatten(&self, bound: &BoundingSpaceN<T, D>) -> BoundingSpaceN<T, D> {
+        let margin = SVector::<T, D>::repeat(self.margin.to_owned());
+        BoundingSpaceN::new(&bound.lower - &margin, &bound.upper + margin)
+    }
+
+    fn allocate(&mut self, node: TreeNode<T, D, V>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, index: usize) {
+        self.nodes[index].children = None;
+        self.nodes[index].parent = None;
+        self.nodes[index].value = None;
+        self.free.push(index);
+    }
+
+    fn is_leaf(&self, index: usize) -> bool {
+        self.nodes[index].children.is_none()
+    }
+
+    fn is_proxy(&self, id: ProxyId) -> bool {
+        self.nodes
+            .get(id.0)
+            .is_some_and(|node| node.value.is_some())
+    }
+
+    pub fn insert(&mut self, bound: &BoundingSpaceN<T, D>, value: V) -> ProxyId {
+        let leaf = self.allocate(TreeNode {
+            fat: self.fatten(bound),
+            parent: None,
+            children: None,
+            height: 0,
+            value: Some(value),
+        });
+        self.insert_leaf(leaf);
+        self.proxies += 1;
+        ProxyId(leaf)
+    }
+
+    pub fn remove(&mut self, id: ProxyId) -> Option<V> {
+        if !self.is_proxy(id) {
+            return None;
+        }
+
+        self.remove_leaf(id.0);
+        let value = self.nodes[id.0].value.take();
+        self.release(id.0);
+        self.proxies -= 1;
+        value
+    }
+
+    /// Updates the bounds of a proxy, reinserting it only when `bound` has
+    /// left the fat bounds. The new fat bounds are stretched along
+    /// `displacement` to anticipate further motion. Returns whether the
+    /// proxy was reinserted.
+    pub fn move_proxy(
+        &mut self,
+        id: ProxyId,
+        bound: &BoundingSpaceN<T, D>,
+        displacement: &SVector<T, D>,
+    ) -> bool {
+        assert!(self.is_proxy(id), "unknown proxy {:?}", id);
+
+        if self.nodes[id.0].fat.contains_space(bound) {
+            return false;
+        }
+
+        self.remove_leaf(id.0);
+
+        let mut fat = self.fatten(bound);
+        for i in 0..D {
+            if displacement[i].is_negative() {
+                fat.lower[i] += displacement[i].to_owned();
+            } else {
+                fat.upper[i] += displacement[i].to_owned();
+            }
+        }
+        self.nodes[id.0].fat = fat;
+
+        self.insert_leaf(id.0);
+        true
+    }
+
+    pub fn get(&self, id: ProxyId) -> Option<&V> {
+        self.nodes.get(id.0).and_then(|node| node.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, id: ProxyId) -> Option<&mut V> {
+        self.nodes
+            .get_mut(id.0)
+            .and_then(|node| node.value.as_mut())
+    }
+
+    pub fn fat_bound(&self, id: ProxyId) -> Option<&BoundingSpaceN<T, D>> {
+        self.is_proxy(id).then(|| &self.nodes[id.0].fat)
+    }
+
+    pub fn height(&self) -> usize {
+        self.root.map_or(0, |root| self.nodes[root].height)
+    }
+
+    pub fn len(&self) -> usize {
+        self.proxies
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Proxies whose fat bounds overlap `bound`.
+    pub fn query(&self, bound: &BoundingSpaceN<T, D>) -> Vec<ProxyId> {
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.fat.intersects(bound) {
+                continue;
+            }
+
+            match node.children {
+                Some(children) => stack.extend(children),
+                None => found.push(ProxyId(index)),
+            }
+        }
+
+        found
+    }
+
+    /// Every pair of proxies with overlapping fat bounds, smaller id first.
+    pub fn overlapping_pairs(&self) -> Vec<(ProxyId, ProxyId)> {
+        let mut pairs = Vec::new();
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node.value.is_none() {
+                continue;
+            }
+
+            for other in self.query(&node.fat) {
+                if index < other.0 {
+                    pairs.push((ProxyId(index), other));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    fn refit(&mut self, index: usize) {
+        let [a, b] = self.nodes[index].children.expect("refit of a leaf");
+        self.nodes[index].fat = self.nodes[a].fat.union(&self.nodes[b].fat);
+        self.nodes[index].height = 1 + self.nodes[a].height.max(self.nodes[b].height);
+    }
+
+    fn replace_child(&mut self, parent: Option<usize>, old: usize, new: usize) {
+        match parent {
+            Some(parent) => {
+                let children = self.nodes[parent]
+                    .children
+                    .as_mut()
+                    .expect("parent is a leaf");
+                if children[0] == old {
+                    children[0] = new;
+                } else {
+                    children[1] = new;
+                }
+            }
+            None => self.root = Some(new),
+        }
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        let Some(mut index) = self.root else {
+            self.root = Some(leaf);
+            self.nodes[leaf].parent = None;
+            return;
+        };
+
+        let fat = self.nodes[leaf].fat.to_owned();
+        let two = T::one() + T::one();
+
+        while let Some([a, b]) = self.nodes[index].children {
+            let area = sah_measure(&self.nodes[index].fat);
+            let combined = sah_measure(&self.nodes[index].fat.union(&fat));
+
+            let cost = two.to_owned() * combined.to_owned();
+            let inheritance = two.to_owned() * (combined - area);
+
+            let descend = |child: usize| {
+                let merged = sah_measure(&fat.union(&self.nodes[child].fat));
+                if self.is_leaf(child) {
+                    merged + inheritance.to_owned()
+                } else {
+                    merged - sah_measure(&self.nodes[child].fat) + inheritance.to_owned()
+                }
+            };
+            let (cost_a, cost_b) = (descend(a), descend(b));
+
+            if cost < cost_a && cost < cost_b {
+                break;
+            }
+
+            index = if cost_a < cost_b { a } else { b };
+        }
+
+        let sibling = index;
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.allocate(TreeNode {
+            fat: fat.union(&self.nodes[sibling].fat),
+            parent: old_parent,
+            children: Some([sibling, leaf]),
+            height: self.nodes[sibling].height + 1,
+            value: None,
+        });
+
+        self.replace_child(old_parent, sibling, new_parent);
+        self.nodes[sibling].parent = Some(new_parent);
+        self.nodes[leaf].parent = Some(new_parent);
+
+        self.fix_upwards(Some(new_parent));
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        if self.root == Some(leaf) {
+            self.root = None;
+            return;
+        }
+
+        let parent = self.nodes[leaf]
+            .parent
+            .expect("non-root leaf without parent");
+        let grand_parent = self.nodes[parent].parent;
+        let [a, b] = self.nodes[parent].children.expect("parent is a leaf");
+        let sibling = if a == leaf { b } else { a };
+
+        self.replace_child(grand_parent, parent, sibling);
+        self.nodes[sibling].parent = grand_parent;
+        self.nodes[leaf].parent = None;
+        self.release(parent);
+
+        self.fix_upwards(grand_parent);
+    }
+
+    fn fix_upwards(&mut self, mut index: Option<usize>) {
+        while let Some(current) = index {
+            let current = self.balance(current);
+            self.refit(current);
+            index = self.nodes[current].parent;
+        }
+    }
+
+    /// Rotates the taller grandchild of `a` up when its children differ in
+    /// height by more than one. Returns the node now at the position of `a`.
+    fn balance(&mut self, a: usize) -> usize {
+        let Some([b, c]) = self.nodes[a].children else {
+            return a;
+        };
+        if self.nodes[a].height < 2 {
+            return a;
+        }
+
+        let (b_height, c_height) = (self.nodes[b].height, self.nodes[c].height);
+
+        if c_height > b_height + 1 {
+            self.rotate_up(a, c, 1)
+        } else if b_height > c_height + 1 {
+            self.rotate_up(a, b, 0)
+        } else {
+            a
+        }
+    }
+
+    /// Promotes `child`, found in slot `side` of `a`, to take the place of
+    /// `a`. The shorter grandchild is handed down to `a`.
+    fn rotate_up(&mut self, a: usize, child: usize, side: usize) -> usize {
+        let [f, g] = self.nodes[child].children.expect("rotating up a leaf");
+        let (taller, shorter) = if self.nodes[f].height > self.nodes[g].height {
+            (f, g)
+        } else {
+            (g, f)
+        };
+
+        let parent = self.nodes[a].parent;
+        self.nodes[child].children = Some([a, taller]);
+        self.nodes[child].parent = parent;
+        self.nodes[a].parent = Some(child);
+        self.replace_child(parent, a, child);
+
+        self.nodes[a].children.as_mut().expect("rotating a leaf")[side] = shorter;
+        self.nodes[shorter].parent = Some(a);
+
+        self.refit(a);
+        self.refit(child);
+
+        child
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Vector2};
+
+    use super::*;
+
+    fn square(x: f64, y: f64) -> BoundingSpaceN<f64, 2> {
+        BoundingSpaceN::new(Point2::new(x, y), Point2::new(x + 1.0, y + 1.0))
+    }
+
+    fn brute_force_pairs(
+        tree: &DynamicTree<f64, 2, usize>,
+        ids: &[ProxyId],
+    ) -> Vec<(ProxyId, ProxyId)> {
+        let mut pairs = Vec::new();
+        for (i, a) in ids.iter().enumerate() {
+            for b in &ids[i + 1..] {
+                if tree
+                    .fat_bound(*a)
+                    .unwrap()
+                    .intersects(tree.fat_bound(*b).unwrap())
+                {
+                    pairs.push((*a.min(b), *a.max(b)));
+                }
+            }
+        }
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn stays_balanced() {
+        let mut tree = DynamicTree::new(0.1);
+        let ids: Vec<_> = (0..256)
+            .map(|i| tree.insert(&square(i as f64 * 2.0, 0.0), i))
+            .collect();
+
+        assert_eq!(tree.len(), 256);
+        assert!(tree.height() <= 16, "height {}", tree.height());
+        assert_eq!(tree.query(&square(10.0, 0.0)), vec![ids[5]]);
+
+        for id in ids.iter().step_by(2) {
+            assert!(tree.remove(*id).is_some());
+        }
+        assert!(tree.remove(ids[0]).is_none());
+        assert_eq!(tree.len(), 128);
+        assert!(tree.height() <= 14, "height {}", tree.height());
+        assert_eq!(tree.get(ids[1]), Some(&1));
+    }
+
+    #[test]
+    fn moves_and_pairs() {
+        let mut tree = DynamicTree::new(0.25);
+        let ids: Vec<_> = (0..20)
+            .map(|i| tree.insert(&square((i % 5) as f64 * 3.0, (i / 5) as f64 * 3.0), i))
+            .collect();
+        assert!(tree.overlapping_pairs().is_empty());
+
+        assert!(!tree.move_proxy(ids[0], &square(0.1, 0.1), &Vector2::new(0.1, 0.1)));
+        assert!(tree.move_proxy(ids[0], &square(2.5, 0.0), &Vector2::new(0.5, -0.1)));
+        assert_relative_eq!(tree.fat_bound(ids[0]).unwrap().upper.x, 3.5 + 0.25 + 0.5);
+        assert_relative_eq!(tree.fat_bound(ids[0]).unwrap().lower.y, -0.25 - 0.1);
+
+        let mut pairs = tree.overlapping_pairs();
+        pairs.sort();
+        assert_eq!(pairs, brute_force_pairs(&tree, &ids));
+        assert_eq!(pairs, vec![(ids[0], ids[1])]);
+    }
+}
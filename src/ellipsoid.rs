@@ -0,0 +1,250 @@
+use core::borrow::Borrow;
+
+use nalgebra::{convert, Point, RealField, SMatrix, SVector};
+
+use crate::linalg::{determinant, symmetric_eigen};
+use crate::{BoundingSpaceN, BoundingSphereN};
+
+/// Ellipsoid of the points `x` with `(x - center)ᵀ · shape · (x - center) <= 1`,
+/// where `shape` is symmetric positive definite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingEllipsoidN<T: RealField, const D: usize> {
+    pub center: Point<T, D>,
+    pub shape: SMatrix<T, D, D>,
+}
+
+pub type BoundingEllipse<T> = BoundingEllipsoidN<T, 2>;
+pub type BoundingEllipsoid<T> = BoundingEllipsoidN<T, 3>;
+
+impl<T: RealField, const D: usize> BoundingEllipsoidN<T, D> {
+    /// Iteration cap of `minimum_volume`.
+    pub const MAX_ITERATIONS: usize = 10_000;
+
+    pub fn new(center: Point<T, D>, shape: SMatrix<T, D, D>) -> Self {
+        Self { center, shape }
+    }
+
+    /// Minimum-volume enclosing ellipsoid by Khachiyan's algorithm, iterated
+    /// until the weights change by less than `tolerance` or for at most
+    /// [`Self::MAX_ITERATIONS`] steps. The result is scaled up if needed so
+    /// every point is contained; when the cap is reached first it still
+    /// encloses the points but may be larger than the minimum. `None` unless
+    /// the points span all `D` dimensions.
+    pub fn minimum_volume<I>(points: I, tolerance: T) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Point<T, D>>,
+    {
+        let points: Vec<Point<T, D>> = points.into_iter().map(|p| p.borrow().to_owned()).collect();
+        let n = points.len();
+        if n <= D {
+            return None;
+        }
+
+        let count: T = convert(n as f64);
+        let mut weights = vec![T::one() / count; n];
+        let lifted_dimension: T = convert((D + 1) as f64);
+
+        // Weighted mean and scatter about it. With the points lifted to
+        // `(p, 1)`, the lifted moment matrix has this scatter as its Schur
+        // complement, so only a D × D inverse is needed per iteration.
+        let moments = |weights: &[T]| {
+            let center = points
+                .iter()
+                .zip(weights)
+                .fold(SVector::<T, D>::zeros(), |acc, (p, w)| {
+                    acc + &p.coords * w.to_owned()
+                });
+            let scatter =
+                points
+                    .iter()
+                    .zip(weights)
+                    .fold(SMatrix::<T, D, D>::zeros(), |acc, (p, w)| {
+                        let offset = &p.coords - &center;
+                        acc + &offset * offset.transpose() * w.to_owned()
+                    });
+            (center, scatter)
+        };
+
+        for _ in 0..Self::MAX_ITERATIONS {
+            let (center, scatter) = moments(&weights);
+            let inverse = scatter.try_inverse()?;
+
+            let (farthest, distance) = points
+                .iter()
+                .map(|p| {
+                    let offset = &p.coords - &center;
+                    T::one() + offset.dot(&(&inverse * &offset))
+                })
+                .enumerate()
+                .fold(
+                    (0, T::zero()),
+                    |best, (j, m)| if m > best.1 { (j, m) } else { best },
+                );
+
+            let step = (distance.to_owned() - lifted_dimension.to_owned())
+                / (lifted_dimension.to_owned() * (distance - T::one()));
+            let mut change = T::zero();
+            for (j, w) in weights.iter_mut().enumerate() {
+                let updated = if j == farthest {
+                    (T::one() - step.to_owned()) * w.to_owned() + step.to_owned()
+                } else {
+                    (T::one() - step.to_owned()) * w.to_owned()
+                };
+                change += (updated.to_owned() - w.to_owned()) * (updated.to_owned() - w.to_owned());
+                *w = updated;
+            }
+
+            if change.sqrt() < tolerance {
+                break;
+            }
+        }
+
+        let (center, scatter) = moments(&weights);
+        let shape = scatter.try_inverse()? / convert::<f64, T>(D as f64);
+
+        // Scaled with a few ulps of slack so rounding in `level` cannot push
+        // the farthest point back outside.
+        let mut ellipsoid = Self::new(center.into(), shape);
+        let reach = points
+            .iter()
+            .fold(T::one(), |acc, p| acc.max(ellipsoid.level(p)));
+        ellipsoid.shape /= reach * (T::one() + T::default_epsilon() * convert(64.0));
+
+        Some(ellipsoid)
+    }
+
+    /// Value of the quadratic form at `point`: below one inside, one on the
+    /// surface.
+    pub fn level(&self, point: &Point<T, D>) -> T {
+        let offset = point - &self.center;
+        offset.dot(&(&self.shape * &offset))
+    }
+
+    pub fn contains(&self, point: &Point<T, D>) -> bool {
+        self.level(point) <= T::one()
+    }
+
+    pub fn volume(&self) -> T {
+        let unit = BoundingSphereN::<T, D>::new(Point::origin(), T::one()).volume();
+        unit / determinant(&self.shape).sqrt()
+    }
+
+    /// Semi-axis lengths, longest first, and the matching unit directions as
+    /// the columns of a rotation.
+    pub fn semi_axes(&self) -> (SVector<T, D>, SMatrix<T, D, D>) {
+        let (values, vectors) = symmetric_eigen(&self.shape);
+        let lengths = values.map(|v| T::one() / v.sqrt());
+        let order: Vec<usize> = (0..D).rev().collect();
+
+        (
+            SVector::<T, D>::from_fn(|i, _| lengths[order[i]].to_owned()),
+            SMatrix::<T, D, D>::from_fn(|r, c| vectors[(r, order[c])].to_owned()),
+        )
+    }
+
+    /// Axis-aligned space enclosing the ellipsoid, touching it on every face.
+    pub fn bounding_space(&self) -> BoundingSpaceN<T, D> {
+        let inverse = self
+            .shape
+            .to_owned()
+            .try_inverse()
+            .unwrap_or_else(SMatrix::zeros);
+        let extents =
+            SVector::<T, D>::from_fn(|i, _| inverse[(i, i)].to_owned().max(T::zero()).sqrt());
+        BoundingSpaceN::new(&self.center - &extents, &self.center + extents)
+    }
+}
+
+impl<T: RealField, const D: usize> BoundingSpaceN<T, D> {
+    /// Largest ellipsoid inside the space, `None` when the space is empty or
+    /// flat along some axis.
+    pub fn inscribed_ellipsoid(&self) -> Option<BoundingEllipsoidN<T, D>> {
+        let half_extents = self.half_extents();
+        if self.is_empty() || half_extents.iter().any(|h| h.is_zero()) {
+            return None;
+        }
+
+        let shape = SMatrix::from_diagonal(&half_extents.map(|h| T::one() / (h.to_owned() * h)));
+        Some(BoundingEllipsoidN::new(self.center(), shape))
+    }
+}
+
+impl<T: RealField, const D: usize> From<BoundingEllipsoidN<T, D>> for BoundingSpaceN<T, D> {
+    fn from(ellipsoid: BoundingEllipsoidN<T, D>) -> Self {
+        ellipsoid.bounding_space()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3, Rotation2, Vector2};
+
+    use super::*;
+
+    #[test]
+    fn khachiyan_recovers_ellipse() {
+        let rotation = Rotation2::new(0.6);
+        let center = Vector2::new(2.0, -1.0);
+        let points: Vec<_> = (0..24)
+            .map(|i| {
+                let angle = i as f64 * core::f64::consts::TAU / 24.0;
+                Point2::from(rotation * Vector2::new(3.0 * angle.cos(), angle.sin()) + center)
+            })
+            .chain([Point2::from(center), Point2::new(2.5, -0.8)])
+            .collect();
+
+        let ellipse = BoundingEllipse::minimum_volume(&points, 1e-9).unwrap();
+        assert!(points.iter().all(|p| ellipse.contains(p)));
+        assert_relative_eq!(ellipse.center, Point2::from(center), epsilon = 1e-3);
+        assert_relative_eq!(
+            ellipse.volume(),
+            3.0 * core::f64::consts::PI,
+            max_relative = 1e-3
+        );
+
+        let (lengths, axes) = ellipse.semi_axes();
+        assert_relative_eq!(lengths, Vector2::new(3.0, 1.0), epsilon = 1e-2);
+        assert!(axes.column(0).dot(&(rotation * Vector2::x())).abs() > 0.9999);
+
+        let space = ellipse.bounding_space();
+        assert!(points.iter().all(|p| space.contains(p)));
+
+        assert!(BoundingEllipse::minimum_volume(
+            [Point2::new(0.0, 0.0), Point2::new(1.0, 1.0)],
+            1e-6
+        )
+        .is_none());
+        let line = [
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(2.0, 2.0),
+        ];
+        assert!(BoundingEllipse::minimum_volume(line, 1e-6).is_none());
+    }
+
+    #[test]
+    fn inscribed_in_space() {
+        let space = BoundingSpaceN::new(Point3::new(0.0, 0.0, 0.0), Point3::new(4.0, 2.0, 6.0));
+        let ellipsoid = space.inscribed_ellipsoid().unwrap();
+
+        assert_relative_eq!(
+            ellipsoid.volume(),
+            4.0 / 3.0 * core::f64::consts::PI * 2.0 * 1.0 * 3.0,
+            epsilon = 1e-12
+        );
+        assert!(ellipsoid.contains(&Point3::new(4.0, 1.0, 3.0)));
+        assert!(!ellipsoid.contains(&Point3::new(3.9, 1.9, 5.9)));
+
+        let back: BoundingSpaceN<f64, 3> = ellipsoid.into();
+        assert_relative_eq!(back.lower, space.lower, epsilon = 1e-12);
+        assert_relative_eq!(back.upper, space.upper, epsilon = 1e-12);
+
+        let flat = BoundingSpaceN::new(Point2::new(0.0, 1.0), Point2::new(2.0, 1.0));
+        assert!(flat.inscribed_ellipsoid().is_none());
+        assert!(BoundingSpaceN::<f64, 2>::empty()
+            .inscribed_ellipsoid()
+            .is_none());
+    }
+}
@@ -0,0 +1,507 @@
+//! Uniform partition of space into axis-aligned cells.
+//!
+//! [`Grid`] covers a bounded domain with a fixed number of cells per axis,
+//! [`SpatialHash`] covers unbounded space by hashing the cells in use.
+
+use core::ops::AddAssign;
+use std::collections::HashMap;
+
+use nalgebra::{convert, try_convert, Point, RealField, SVector};
+
+use crate::{BoundingSpaceN, Ray};
+
+fn floor_to_i64<T: RealField>(value: T) -> i64 {
+    try_convert::<T, f64>(value.floor()).unwrap_or(0.0) as i64
+}
+
+/// Iterator over every index in the inclusive box `lower..=upper`.
+#[derive(Debug, Clone)]
+pub struct CellRange<I, const D: usize> {
+    lower: [I; D],
+    upper: [I; D],
+    next: Option<[I; D]>,
+}
+
+impl<I: Copy + PartialOrd + AddAssign + From<u8>, const D: usize> CellRange<I, D> {
+    fn new(lower: [I; D], upper: [I; D]) -> Self {
+        let non_empty = lower.iter().zip(&upper).all(|(l, u)| l <= u);
+        Self {
+            lower,
+            upper,
+            next: non_empty.then_some(lower),
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            lower: [I::from(0); D],
+            upper: [I::from(0); D],
+            next: None,
+        }
+    }
+}
+
+impl<const D: usize> CellRange<i64, D> {
+    /// Number of cells in the whole range, saturating at `u128::MAX`.
+    fn total(&self) -> u128 {
+        if self.next.is_none() {
+            return 0;
+        }
+
+        self.lower
+            .iter()
+            .zip(&self.upper)
+            .fold(1, |acc: u128, (l, u)| {
+                acc.saturating_mul((*u as i128 - *l as i128 + 1) as u128)
+            })
+    }
+}
+
+impl<I: Copy + PartialOrd + AddAssign + From<u8>, const D: usize> Iterator for CellRange<I, D> {
+    type Item = [I; D];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        let mut following = current;
+
+        self.next = None;
+        for i in 0..D {
+            if following[i] < self.upper[i] {
+                following[i] += I::from(1);
+                self.next = Some(following);
+                break;
+            }
+            following[i] = self.lower[i];
+        }
+
+        Some(current)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Grid<T: RealField, const D: usize> {
+    domain: BoundingSpaceN<T, D>,
+    counts: [usize; D],
+    cell_size: SVector<T, D>,
+}
+
+impl<T: RealField, const D: usize> Grid<T, D> {
+    pub fn with_counts(domain: BoundingSpaceN<T, D>, counts: [usize; D]) -> Self {
+        assert!(!domain.is_empty(), "grid over an empty domain");
+        assert!(counts.iter().all(|&c| c > 0), "every axis needs a cell");
+
+        let cell_size =
+            SVector::from_fn(|i, _| domain.diagonal()[i].to_owned() / convert(counts[i] as f64));
+
+        Self {
+            domain,
+            counts,
+            cell_size,
+        }
+    }
+
+    /// Grid of cells of the given size, the last cell of an axis reaching
+    /// past the domain when the size does not divide its extent.
+    pub fn with_cell_size(domain: BoundingSpaceN<T, D>, cell_size: SVector<T, D>) -> Self {
+        assert!(!domain.is_empty(), "grid over an empty domain");
+        assert!(
+            cell_size.iter().all(|s| s.is_positive()),
+            "cell size must be positive"
+        );
+
+        let diagonal = domain.diagonal();
+        let counts = core::array::from_fn(|i| {
+            let cells = (diagonal[i].to_owned() / cell_size[i].to_owned()).ceil();
+            (floor_to_i64(cells) as usize).max(1)
+        });
+        let upper = &domain.lower.coords
+            + SVector::<T, D>::from_fn(|i, _| {
+                cell_size[i].to_owned() * convert::<f64, T>(counts[i] as f64)
+            });
+
+        Self {
+            domain: BoundingSpaceN::new(domain.lower, upper.into()),
+            counts,
+            cell_size,
+        }
+    }
+
+    pub fn domain(&self) -> &BoundingSpaceN<T, D> {
+        &self.domain
+    }
+
+    pub fn counts(&self) -> [usize; D] {
+        self.counts
+    }
+
+    pub fn cell_size(&self) -> &SVector<T, D> {
+        &self.cell_size
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.counts.iter().product()
+    }
+
+    /// Cell index along each axis, clamped into the grid.
+    fn clamped_cell(&self, point: &Point<T, D>) -> [usize; D] {
+        core::array::from_fn(|i| {
+            let offset = (point[i].to_owned() - self.domain.lower[i].to_owned())
+                / self.cell_size[i].to_owned();
+            floor_to_i64(offset).clamp(0, self.counts[i] as i64 - 1) as usize
+        })
+    }
+
+    /// Cell containing `point`, `None` outside the domain. Points on the
+    /// upper boundary belong to the last cell.
+    pub fn cell_of(&self, point: &Point<T, D>) -> Option<[usize; D]> {
+        self.domain
+            .contains(point)
+            .then(|| self.clamped_cell(point))
+    }
+
+    pub fn linear_index(&self, cell: &[usize; D]) -> usize {
+        (0..D)
+            .rev()
+            .fold(0, |index, i| index * self.counts[i] + cell[i])
+    }
+
+    pub fn cell_bound(&self, cell: &[usize; D]) -> BoundingSpaceN<T, D> {
+        let lower = SVector::<T, D>::from_fn(|i, _| {
+            self.domain.lower[i].to_owned()
+                + self.cell_size[i].to_owned() * convert::<f64, T>(cell[i] as f64)
+        });
+        let upper = &lower + &self.cell_size;
+        BoundingSpaceN::new(lower.into(), upper.into())
+    }
+
+    /// Cells overlapped by `bound`, clipped to the grid.
+    pub fn cells_overlapping(&self, bound: &BoundingSpaceN<T, D>) -> CellRange<usize, D> {
+        match self.domain.intersection(bound) {
+            Some(clipped) => CellRange::new(
+                self.clamped_cell(&clipped.lower),
+                self.clamped_cell(&clipped.upper),
+            ),
+            None => CellRange::empty(),
+        }
+    }
+
+    /// Cells crossed by the ray within `[t_min, t_max]` in the order they are
+    /// entered (Amanatides and Woo).
+    pub fn traverse_ray(&self, ray: &Ray<T, D>, t_min: T, t_max: T) -> RayCells<T, D> {
+        let Some((enter, exit)) = self.domain.ray_intersection(ray, t_min, t_max) else {
+            return RayCells {
+                cell: [0; D],
+                step: [0; D],
+                t_next: SVector::zeros(),
+                t_delta: SVector::zeros(),
+                counts: self.counts,
+                enter: T::zero(),
+                exit: T::zero(),
+                done: true,
+            };
+        };
+
+        let cell = self.clamped_cell(&ray.point_at(enter.to_owned()));
+        let max = crate::max_value::<T>();
+        let mut step = [0; D];
+        let mut t_next = SVector::repeat(max.to_owned());
+        let mut t_delta = SVector::repeat(max);
+
+        for i in 0..D {
+            let direction = ray.direction[i].to_owned();
+            if direction.is_zero() {
+                continue;
+            }
+
+            let cell_lower = self.domain.lower[i].to_owned()
+                + self.cell_size[i].to_owned() * convert::<f64, T>(cell[i] as f64);
+            let boundary = if direction.is_positive() {
+                step[i] = 1;
+                cell_lower + self.cell_size[i].to_owned()
+            } else {
+                step[i] = -1;
+                cell_lower
+            };
+
+            t_next[i] = (boundary - ray.origin[i].to_owned()) / direction.to_owned();
+            t_delta[i] = self.cell_size[i].to_owned() / direction.abs();
+        }
+
+        RayCells {
+            cell,
+            step,
+            t_next,
+            t_delta,
+            counts: self.counts,
+            enter,
+            exit,
+            done: false,
+        }
+    }
+}
+
+/// Cells along a ray, yielded with the ray parameter at which each is entered.
+#[derive(Debug, Clone)]
+pub struct RayCells<T: RealField, const D: usize> {
+    cell: [usize; D],
+    step: [i8; D],
+    t_next: SVector<T, D>,
+    t_delta: SVector<T, D>,
+    counts: [usize; D],
+    enter: T,
+    exit: T,
+    done: bool,
+}
+
+impl<T: RealField, const D: usize> Iterator for RayCells<T, D> {
+    type Item = ([usize; D], T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let current = (self.cell, self.enter.to_owned());
+
+        let axis = (0..D).fold(0, |best, i| {
+            if self.t_next[i] < self.t_next[best] {
+                i
+            } else {
+                best
+            }
+        });
+
+        if D == 0 || self.step[axis] == 0 || self.t_next[axis] > self.exit {
+            self.done = true;
+            return Some(current);
+        }
+
+        let moved = self.cell[axis] as i64 + self.step[axis] as i64;
+        if moved < 0 || moved >= self.counts[axis] as i64 {
+            self.done = true;
+        } else {
+            self.cell[axis] = moved as usize;
+            self.enter = self.t_next[axis].to_owned();
+            self.t_next[axis] += self.t_delta[axis].to_owned();
+        }
+
+        Some(current)
+    }
+}
+
+/// Sparse grid over unbounded space, storing only the cells in use.
+///
+/// Entries covering more than `max_cells` cells are kept in a separate list
+/// that every query scans, so a huge or unbounded box costs one comparison
+/// per query instead of a cell per covered cell.
+#[derive(Debug, Clone)]
+pub struct SpatialHash<T: RealField, const D: usize, V> {
+    cell_size: SVector<T, D>,
+    max_cells: usize,
+    cells: HashMap<[i64; D], Vec<usize>>,
+    oversized: Vec<usize>,
+    entries: Vec<(BoundingSpaceN<T, D>, V)>,
+}
+
+impl<T: RealField, const D: usize, V> SpatialHash<T, D, V> {
+    pub const DEFAULT_MAX_CELLS: usize = 4096;
+
+    pub fn new(cell_size: SVector<T, D>) -> Self {
+        Self::with_max_cells(cell_size, Self::DEFAULT_MAX_CELLS)
+    }
+
+    pub fn with_max_cells(cell_size: SVector<T, D>, max_cells: usize) -> Self {
+        assert!(
+            cell_size.iter().all(|s| s.is_positive()),
+            "cell size must be positive"
+        );
+
+        Self {
+            cell_size,
+            max_cells,
+            cells: HashMap::new(),
+            oversized: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn cell_of(&self, point: &Point<T, D>) -> [i64; D] {
+        core::array::from_fn(|i| floor_to_i64(point[i].to_owned() / self.cell_size[i].to_owned()))
+    }
+
+    pub fn cell_bound(&self, cell: &[i64; D]) -> BoundingSpaceN<T, D> {
+        let lower = SVector::<T, D>::from_fn(|i, _| {
+            self.cell_size[i].to_owned() * convert::<f64, T>(cell[i] as f64)
+        });
+        let upper = &lower + &self.cell_size;
+        BoundingSpaceN::new(lower.into(), upper.into())
+    }
+
+    pub fn cells_overlapping(&self, bound: &BoundingSpaceN<T, D>) -> CellRange<i64, D> {
+        if bound.is_empty() {
+            return CellRange::empty();
+        }
+
+        CellRange::new(self.cell_of(&bound.lower), self.cell_of(&bound.upper))
+    }
+
+    pub fn insert(&mut self, bound: BoundingSpaceN<T, D>, value: V) {
+        let index = self.entries.len();
+        let range = self.cells_overlapping(&bound);
+        if range.total() > self.max_cells as u128 {
+            self.oversized.push(index);
+        } else {
+            for cell in range {
+                self.cells.entry(cell).or_default().push(index);
+            }
+        }
+        self.entries.push((bound, value));
+    }
+
+    pub fn insert_point(&mut self, point: Point<T, D>, value: V) {
+        self.insert(BoundingSpaceN::from_point(point), value);
+    }
+
+    /// Values whose bounds overlap `bound`, each reported once. Windows
+    /// covering more cells than are in use fall back to scanning every entry.
+    pub fn query_overlap(&self, bound: &BoundingSpaceN<T, D>) -> Vec<&V> {
+        let range = self.cells_overlapping(bound);
+        let candidates: Vec<usize> = if range.total() > self.cells.len() as u128 {
+            (0..self.entries.len()).collect()
+        } else {
+            range
+                .filter_map(|cell| self.cells.get(&cell))
+                .flatten()
+                .chain(&self.oversized)
+                .copied()
+                .collect()
+        };
+
+        let mut indices: Vec<usize> = candidates
+            .into_iter()
+            .filter(|&i| self.entries[i].0.intersects(bound))
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        indices.into_iter().map(|i| &self.entries[i].1).collect()
+    }
+
+    pub fn query_point(&self, point: &Point<T, D>) -> Vec<&V> {
+        self.cells
+            .get(&self.cell_of(point))
+            .into_iter()
+            .flatten()
+            .chain(&self.oversized)
+            .filter(|&&i| self.entries[i].0.contains(point))
+            .map(|&i| &self.entries[i].1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3, Vector2, Vector3};
+
+    use super::*;
+
+    fn unit_grid() -> Grid<f64, 2> {
+        let domain = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(4.0, 2.0));
+        Grid::with_counts(domain, [4, 2])
+    }
+
+    #[test]
+    fn cells_and_bounds() {
+        let grid = unit_grid();
+
+        assert_eq!(grid.cell_of(&Point2::new(2.5, 0.5)), Some([2, 0]));
+        assert_eq!(grid.cell_of(&Point2::new(4.0, 2.0)), Some([3, 1]));
+        assert_eq!(grid.cell_of(&Point2::new(4.5, 1.0)), None);
+        assert_eq!(grid.linear_index(&[2, 1]), 6);
+        assert_relative_eq!(grid.cell_bound(&[2, 1]).lower, Point2::new(2.0, 1.0));
+
+        let window = BoundingSpaceN::new(Point2::new(0.5, 0.5), Point2::new(2.5, 5.0));
+        let cells: Vec<_> = grid.cells_overlapping(&window).collect();
+        assert_eq!(cells, vec![[0, 0], [1, 0], [2, 0], [0, 1], [1, 1], [2, 1]]);
+
+        let sized = Grid::with_cell_size(*grid.domain(), Vector2::new(1.5, 1.5));
+        assert_eq!(sized.counts(), [3, 2]);
+        assert_relative_eq!(sized.domain().upper, Point2::new(4.5, 3.0));
+    }
+
+    #[test]
+    fn ray_walk() {
+        let grid = unit_grid();
+
+        let ray = Ray::new(Point2::new(-1.0, 0.25), Vector2::new(1.0, 0.5));
+        let cells: Vec<_> = grid.traverse_ray(&ray, 0.0, f64::MAX).collect();
+        let indices: Vec<_> = cells.iter().map(|(c, _)| *c).collect();
+        assert_eq!(indices, vec![[0, 0], [0, 1], [1, 1], [2, 1]]);
+        assert_relative_eq!(cells[0].1, 1.0);
+        assert_relative_eq!(cells[1].1, 1.5);
+        assert_relative_eq!(cells[3].1, 3.0);
+
+        let axis_parallel = Ray::new(Point2::new(0.5, 1.5), Vector2::new(0.0, -1.0));
+        let cells: Vec<_> = grid
+            .traverse_ray(&axis_parallel, 0.0, f64::MAX)
+            .map(|(c, _)| c)
+            .collect();
+        assert_eq!(cells, vec![[0, 1], [0, 0]]);
+
+        let miss = Ray::new(Point2::new(5.0, 5.0), Vector2::new(1.0, 0.0));
+        assert_eq!(grid.traverse_ray(&miss, 0.0, f64::MAX).count(), 0);
+    }
+
+    #[test]
+    fn spatial_hash() {
+        let mut hash = SpatialHash::new(Vector3::repeat(2.0));
+        hash.insert(
+            BoundingSpaceN::new(Point3::new(-3.0, -1.0, 0.0), Point3::new(3.0, 1.0, 1.0)),
+            'a',
+        );
+        hash.insert_point(Point3::new(100.0, -50.0, 7.0), 'b');
+        hash.insert_point(Point3::new(-0.5, 0.5, 0.5), 'c');
+
+        assert_eq!(hash.cell_of(&Point3::new(-0.5, 3.0, 4.0)), [-1, 1, 2]);
+        assert_eq!(
+            hash.query_point(&Point3::new(100.0, -50.0, 7.0)),
+            vec![&'b']
+        );
+
+        let window = BoundingSpaceN::new(Point3::new(-1.0, 0.0, 0.0), Point3::new(0.0, 1.0, 1.0));
+        assert_eq!(hash.query_overlap(&window), vec![&'a', &'c']);
+        assert_eq!(hash.len(), 3);
+    }
+
+    #[test]
+    fn oversized_entries() {
+        let mut hash = SpatialHash::with_max_cells(Vector2::repeat(1.0), 16);
+        let mut unbounded = BoundingSpaceN::empty();
+        unbounded.expand(&Point2::new(-f64::MAX, -f64::MAX));
+        unbounded.expand(&Point2::new(f64::MAX, 1.0));
+        hash.insert(unbounded, 'u');
+        hash.insert(
+            BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(10.0, 0.5)),
+            'w',
+        );
+        hash.insert_point(Point2::new(3.5, 2.5), 'p');
+
+        assert_eq!(hash.query_point(&Point2::new(3.5, 0.5)), vec![&'w', &'u']);
+        assert_eq!(hash.query_point(&Point2::new(3.5, 2.5)), vec![&'p']);
+        let window = BoundingSpaceN::new(Point2::new(3.0, 0.0), Point2::new(4.0, 3.0));
+        assert_eq!(hash.query_overlap(&window), vec![&'u', &'w', &'p']);
+
+        let everything =
+            BoundingSpaceN::new(Point2::new(-1e300, -1e300), Point2::new(1e300, 1e300));
+        assert_eq!(hash.query_overlap(&everything).len(), 3);
+    }
+}
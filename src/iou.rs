@@ -0,0 +1,211 @@
+//! Overlap metrics used to compare detection boxes.
+//!
+//! Every metric is defined for any dimension. When either space is empty
+//! IoU is zero and the generalised metrics return their lower bound of `-1`.
+//! When both are non-empty but the union has zero volume, IoU is zero, GIoU
+//! drops its hull term if the enclosing space is flat as well, and DIoU and
+//! CIoU keep their center distance penalty; two equal points score zero.
+
+use nalgebra::{convert, RealField};
+
+use crate::BoundingSpaceN;
+
+impl<T: RealField, const D: usize> BoundingSpaceN<T, D> {
+    pub fn intersection_volume(&self, other: &Self) -> T {
+        self.intersection(other)
+            .map(|bound| bound.volume())
+            .unwrap_or_else(T::zero)
+    }
+
+    pub fn union_volume(&self, other: &Self) -> T {
+        self.volume() + other.volume() - self.intersection_volume(other)
+    }
+
+    pub fn iou(&self, other: &Self) -> T {
+        let union = self.union_volume(other);
+
+        if self.is_empty() || other.is_empty() || union.is_zero() {
+            return T::zero();
+        }
+
+        self.intersection_volume(other) / union
+    }
+
+    /// Generalised IoU of Rezatofighi et al.
+    pub fn giou(&self, other: &Self) -> T {
+        if self.is_empty() || other.is_empty() {
+            return -T::one();
+        }
+
+        let iou = self.iou(other);
+        let hull = self.union(other).volume();
+
+        if hull.is_zero() {
+            return iou;
+        }
+
+        iou - (hull.to_owned() - self.union_volume(other)) / hull
+    }
+
+    /// Distance IoU: IoU penalised by the squared center distance relative to
+    /// the squared diagonal of the enclosing space.
+    pub fn diou(&self, other: &Self) -> T {
+        if self.is_empty() || other.is_empty() {
+            return -T::one();
+        }
+
+        self.iou(other) - self.center_penalty(other)
+    }
+
+    /// Complete IoU. The aspect ratio term is averaged over every pair of
+    /// axes, which reduces to the usual width/height term in 2D and
+    /// vanishes in 1D.
+    pub fn ciou(&self, other: &Self) -> T {
+        if self.is_empty() || other.is_empty() {
+            return -T::one();
+        }
+
+        let iou = self.iou(other);
+        let v = self.aspect_consistency(other);
+        let denominator = T::one() - iou.to_owned() + v.to_owned();
+        let alpha = if denominator.is_zero() {
+            T::zero()
+        } else {
+            v.to_owned() / denominator
+        };
+
+        iou - self.center_penalty(other) - alpha * v
+    }
+
+    fn center_penalty(&self, other: &Self) -> T {
+        let diagonal = self.union(other).diagonal().norm_squared();
+
+        if diagonal.is_zero() {
+            return T::zero();
+        }
+
+        (self.center() - other.center()).norm_squared() / diagonal
+    }
+
+    fn aspect_consistency(&self, other: &Self) -> T {
+        let (a, b) = (self.diagonal(), other.diagonal());
+        let mut sum = T::zero();
+        let mut pairs = 0;
+
+        for i in 0..D {
+            for j in i + 1..D {
+                let delta =
+                    a[i].to_owned().atan2(a[j].to_owned()) - b[i].to_owned().atan2(b[j].to_owned());
+                sum += delta.to_owned() * delta;
+                pairs += 1;
+            }
+        }
+
+        if pairs == 0 {
+            return T::zero();
+        }
+
+        let scale: T = convert(4.0);
+        scale / (T::pi() * T::pi()) * sum / convert(pairs as f64)
+    }
+}
+
+/// Matrix of `metric(a[i], b[j])` stored as `rows[i][j]`.
+pub fn pairwise<T, const D: usize, F>(
+    a: &[BoundingSpaceN<T, D>],
+    b: &[BoundingSpaceN<T, D>],
+    metric: F,
+) -> Vec<Vec<T>>
+where
+    T: RealField,
+    F: Fn(&BoundingSpaceN<T, D>, &BoundingSpaceN<T, D>) -> T,
+{
+    a.iter()
+        .map(|x| b.iter().map(|y| metric(x, y)).collect())
+        .collect()
+}
+
+pub fn iou_matrix<T: RealField, const D: usize>(
+    a: &[BoundingSpaceN<T, D>],
+    b: &[BoundingSpaceN<T, D>],
+) -> Vec<Vec<T>> {
+    pairwise(a, b, BoundingSpaceN::iou)
+}
+
+pub fn giou_matrix<T: RealField, const D: usize>(
+    a: &[BoundingSpaceN<T, D>],
+    b: &[BoundingSpaceN<T, D>],
+) -> Vec<Vec<T>> {
+    pairwise(a, b, BoundingSpaceN::giou)
+}
+
+pub fn diou_matrix<T: RealField, const D: usize>(
+    a: &[BoundingSpaceN<T, D>],
+    b: &[BoundingSpaceN<T, D>],
+) -> Vec<Vec<T>> {
+    pairwise(a, b, BoundingSpaceN::diou)
+}
+
+pub fn ciou_matrix<T: RealField, const D: usize>(
+    a: &[BoundingSpaceN<T, D>],
+    b: &[BoundingSpaceN<T, D>],
+) -> Vec<Vec<T>> {
+    pairwise(a, b, BoundingSpaceN::ciou)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3};
+
+    use super::*;
+    use crate::BoundingSquare;
+
+    fn square(x: f32, y: f32, w: f32, h: f32) -> BoundingSquare<f32> {
+        BoundingSpaceN::new(Point2::new(x, y), Point2::new(x + w, y + h))
+    }
+
+    #[test]
+    fn iou_family() {
+        let a = square(0.0, 0.0, 2.0, 2.0);
+        let b = square(1.0, 0.0, 2.0, 2.0);
+
+        assert_relative_eq!(a.iou(&a), 1.0);
+        assert_relative_eq!(a.iou(&b), 2.0 / 6.0);
+        assert_relative_eq!(a.giou(&b), 2.0 / 6.0);
+        assert_relative_eq!(a.diou(&b), 2.0 / 6.0 - 1.0 / 13.0);
+        assert_relative_eq!(a.ciou(&b), a.diou(&b));
+
+        let far = square(4.0, 0.0, 2.0, 2.0);
+        assert_relative_eq!(a.iou(&far), 0.0);
+        assert_relative_eq!(a.giou(&far), -2.0 / 6.0);
+
+        let tall = square(0.0, 0.0, 1.0, 4.0);
+        assert!(a.ciou(&tall) < a.diou(&tall));
+    }
+
+    #[test]
+    fn degenerate_and_empty() {
+        let point = square(1.0, 1.0, 0.0, 0.0);
+        let empty = BoundingSquare::<f32>::empty();
+
+        assert_relative_eq!(point.iou(&point), 0.0);
+        assert_relative_eq!(point.giou(&point), 0.0);
+        assert_relative_eq!(point.diou(&point), 0.0);
+        assert_relative_eq!(point.iou(&empty), 0.0);
+        assert_relative_eq!(empty.giou(&point), -1.0);
+        assert_relative_eq!(empty.ciou(&empty), -1.0);
+    }
+
+    #[test]
+    fn lidar_boxes_and_matrix() {
+        let a = BoundingSpaceN::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 2.0, 2.0));
+        let b = BoundingSpaceN::new(Point3::new(1.0, 1.0, 1.0), Point3::new(3.0, 3.0, 3.0));
+        assert_relative_eq!(a.iou(&b), 1.0 / 15.0);
+
+        let matrix = iou_matrix(&[a, b], &[b]);
+        assert_eq!(matrix.len(), 2);
+        assert_relative_eq!(matrix[0][0], 1.0 / 15.0);
+        assert_relative_eq!(matrix[1][0], 1.0);
+    }
+}
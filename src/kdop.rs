@@ -0,0 +1,275 @@
+use core::borrow::Borrow;
+
+use nalgebra::{Point, RealField, SVector};
+
+use crate::{max_value, BoundingSpaceN};
+
+/// Discrete oriented polytope bounded by `K` slabs, each given by the range
+/// of `direction · p` over the contained points. The first `D` directions
+/// are the coordinate axes, so the polytope refines `BoundingSpaceN`, which
+/// is the case `K == D`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KDop<T: RealField, const D: usize, const K: usize> {
+    pub lower: SVector<T, K>,
+    pub upper: SVector<T, K>,
+}
+
+pub type Dop8<T> = KDop<T, 2, 4>;
+pub type Dop14<T> = KDop<T, 3, 7>;
+pub type Dop18<T> = KDop<T, 3, 9>;
+pub type Dop26<T> = KDop<T, 3, 13>;
+
+/// The first `K` of the non-zero vectors over `{-1, 0, 1}^D` with a positive
+/// first non-zero component, ordered by the number of non-zero components
+/// and then with positive components first. When `K` is `D + 2^(D-1)` only
+/// the axes and the cube diagonals are taken. Evaluated at compile time, so
+/// an unsupported `K` fails the build.
+const fn slab_table<const D: usize, const K: usize>() -> [[i8; D]; K] {
+    let mut codes = 1;
+    let mut i = 0;
+    while i < D {
+        codes *= 3;
+        i += 1;
+    }
+    let diagonals = D > 1 && K == D + (1 << (D - 1));
+
+    let mut table = [[0; D]; K];
+    let mut count = 0;
+    let mut nonzero = 1;
+    while nonzero <= D && count < K {
+        if !diagonals || nonzero == 1 || nonzero == D {
+            // Descending codes give descending lexicographic order, with
+            // component 0 as the most significant digit.
+            let mut code = codes;
+            while code > 0 && count < K {
+                code -= 1;
+                let mut direction = [0; D];
+                let (mut rest, mut found, mut leading) = (code, 0, 0);
+                let mut i = D;
+                while i > 0 {
+                    i -= 1;
+                    direction[i] = (rest % 3) as i8 - 1;
+                    rest /= 3;
+                    if direction[i] != 0 {
+                        found += 1;
+                        leading = direction[i];
+                    }
+                }
+                if found == nonzero && leading > 0 {
+                    table[count] = direction;
+                    count += 1;
+                }
+            }
+        }
+        nonzero += 1;
+    }
+
+    assert!(K >= D && count == K, "unsupported number of DOP slabs");
+    table
+}
+
+/// `direction · point` for a lattice direction.
+fn project<T: RealField, const D: usize>(direction: &[i8; D], point: &Point<T, D>) -> T {
+    direction
+        .iter()
+        .zip(point.iter())
+        .fold(T::zero(), |acc, (c, x)| match c {
+            1 => acc + x.to_owned(),
+            -1 => acc - x.to_owned(),
+            _ => acc,
+        })
+}
+
+impl<T: RealField, const D: usize, const K: usize> KDop<T, D, K> {
+    const SLABS: [[i8; D]; K] = slab_table::<D, K>();
+
+    /// Slab directions: the axes, then either the cube diagonals when `K` is
+    /// `D + 2^(D-1)` (8-DOP in 2D, 14-DOP in 3D) or the remaining lattice
+    /// directions by number of non-zero components (18- and 26-DOP in 3D).
+    pub fn directions() -> [SVector<T, D>; K] {
+        Self::SLABS.map(|d| SVector::<T, D>::from_fn(|i, _| nalgebra::convert(d[i] as f64)))
+    }
+
+    pub fn empty() -> Self {
+        let max = max_value::<T>();
+        Self {
+            lower: SVector::repeat(max.to_owned()),
+            upper: SVector::repeat(-max),
+        }
+    }
+
+    pub fn from_point(point: &Point<T, D>) -> Self {
+        let mut dop = Self::empty();
+        dop.expand(point);
+        dop
+    }
+
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Point<T, D>>,
+    {
+        let mut points = points.into_iter().peekable();
+        points.peek()?;
+
+        let mut dop = Self::empty();
+        for point in points {
+            dop.expand(point.borrow());
+        }
+        Some(dop)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lower.iter().zip(&self.upper).any(|(l, u)| l > u)
+    }
+
+    pub fn expand(&mut self, point: &Point<T, D>) {
+        for (k, direction) in Self::SLABS.iter().enumerate() {
+            let value = project(direction, point);
+            self.lower[k] = self.lower[k].to_owned().min(value.to_owned());
+            self.upper[k] = self.upper[k].to_owned().max(value);
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.lower
+            .zip_apply(&other.lower, |l, o| *l = o.min(l.to_owned()));
+        self.upper
+            .zip_apply(&other.upper, |u, o| *u = o.max(u.to_owned()));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut dop = self.to_owned();
+        dop.merge(other);
+        dop
+    }
+
+    /// Overlap of every slab. Like the box test this is exact for the axis
+    /// slabs alone, and conservative once diagonal slabs are involved.
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.lower.iter().zip(&other.upper).all(|(l, u)| l <= u)
+            && other.lower.iter().zip(&self.upper).all(|(l, u)| l <= u)
+    }
+
+    pub fn contains(&self, point: &Point<T, D>) -> bool {
+        Self::SLABS.iter().enumerate().all(|(k, direction)| {
+            let value = project(direction, point);
+            self.lower[k] <= value && value <= self.upper[k]
+        })
+    }
+
+    /// Axis slabs as a `BoundingSpaceN`, which always encloses the polytope.
+    pub fn bounding_space(&self) -> BoundingSpaceN<T, D> {
+        BoundingSpaceN::new(
+            SVector::<T, D>::from_fn(|i, _| self.lower[i].to_owned()).into(),
+            SVector::<T, D>::from_fn(|i, _| self.upper[i].to_owned()).into(),
+        )
+    }
+}
+
+/// Slabs of the polytope circumscribing the space, so the conversion back
+/// gives the same space.
+impl<T: RealField, const D: usize, const K: usize> From<BoundingSpaceN<T, D>> for KDop<T, D, K> {
+    fn from(space: BoundingSpaceN<T, D>) -> Self {
+        if space.is_empty() {
+            return Self::empty();
+        }
+
+        let mut dop = Self::empty();
+        for (k, direction) in Self::SLABS.iter().enumerate() {
+            let (mut lower, mut upper) = (T::zero(), T::zero());
+            for (i, c) in direction.iter().enumerate() {
+                let (a, b) = match c {
+                    1 => (space.lower[i].to_owned(), space.upper[i].to_owned()),
+                    -1 => (-space.upper[i].to_owned(), -space.lower[i].to_owned()),
+                    _ => continue,
+                };
+                lower += a;
+                upper += b;
+            }
+            dop.lower[k] = lower;
+            dop.upper[k] = upper;
+        }
+        dop
+    }
+}
+
+impl<T: RealField, const D: usize, const K: usize> From<KDop<T, D, K>> for BoundingSpaceN<T, D> {
+    fn from(dop: KDop<T, D, K>) -> Self {
+        dop.bounding_space()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3, Vector2, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn standard_directions() {
+        let dop8 = Dop8::<f64>::directions();
+        assert_eq!(dop8[2], Vector2::new(1.0, 1.0));
+        assert_eq!(dop8[3], Vector2::new(1.0, -1.0));
+
+        let dop14 = Dop14::<f64>::directions();
+        assert!(dop14[3..].iter().all(|d| d.iter().all(|c| c.abs() == 1.0)));
+        let dop18 = Dop18::<f64>::directions();
+        assert!(dop18[3..]
+            .iter()
+            .all(|d| d.iter().filter(|c| **c == 0.0).count() == 1));
+        let dop26 = Dop26::<f64>::directions();
+        assert_eq!(dop26[..9], dop18[..]);
+        assert!(dop26[9..].iter().all(|d| d.iter().all(|c| c.abs() == 1.0)));
+        assert_eq!(
+            KDop::<f64, 3, 3>::directions()[..],
+            [Vector3::x(), Vector3::y(), Vector3::z()]
+        );
+    }
+
+    #[test]
+    fn tighter_than_box() {
+        let triangle = [
+            Point2::new(0.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(0.0, 2.0),
+        ];
+        let dop = Dop8::from_points(triangle).unwrap();
+        let space = BoundingSpaceN::from_points(triangle).unwrap();
+
+        let corner = Point2::new(1.8, 1.8);
+        assert!(space.contains(&corner));
+        assert!(!dop.contains(&corner));
+        assert!(triangle.iter().all(|p| dop.contains(p)));
+        assert_relative_eq!(dop.bounding_space().lower, space.lower);
+        assert_relative_eq!(dop.bounding_space().upper, space.upper);
+
+        let other = Dop8::from_points([Point2::new(1.6, 1.6), Point2::new(3.0, 3.0)]).unwrap();
+        assert!(space.intersects(&other.bounding_space()));
+        assert!(!dop.intersects(&other));
+
+        let mut merged = dop;
+        merged.merge(&other);
+        assert!(merged.contains(&Point2::new(3.0, 3.0)) && merged.contains(&Point2::new(2.0, 0.0)));
+        assert!(Dop8::<f64>::from_points(Vec::<Point2<f64>>::new()).is_none());
+    }
+
+    #[test]
+    fn space_round_trip() {
+        let space = BoundingSpaceN::new(Point3::new(-1.0, 0.0, 2.0), Point3::new(1.0, 3.0, 5.0));
+        let dop: Dop26<f64> = space.into();
+        assert!(space.corners().all(|c| dop.contains(&c)));
+        assert_eq!(dop, Dop26::from_points(space.corners()).unwrap());
+
+        let back: BoundingSpaceN<f64, 3> = dop.into();
+        assert_relative_eq!(back.lower, space.lower);
+        assert_relative_eq!(back.upper, space.upper);
+
+        let empty: Dop14<f64> = BoundingSpaceN::empty().into();
+        assert!(empty.is_empty());
+        let mut grown = empty;
+        grown.expand(&Point3::new(1.0, 1.0, 1.0));
+        assert!(!grown.is_empty() && grown.contains(&Point3::new(1.0, 1.0, 1.0)));
+    }
+}
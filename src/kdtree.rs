@@ -0,0 +1,324 @@
+//! k-d tree over a fixed point set.
+//!
+//! Every node splits its cell at the median point along the longest axis of
+//! the cell and keeps the cell bounds, so queries can prune whole subtrees
+//! with the box distance and overlap tests of `BoundingSpaceN`. Query
+//! results refer to points by their index in the input.
+
+use core::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use nalgebra::{Point, RealField};
+
+use crate::{total_cmp, BoundingSpaceN};
+
+#[derive(Debug, Clone)]
+struct KdNode<T: RealField, const D: usize> {
+    point: usize,
+    axis: usize,
+    cell: BoundingSpaceN<T, D>,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct KdTree<T: RealField, const D: usize> {
+    points: Vec<Point<T, D>>,
+    nodes: Vec<KdNode<T, D>>,
+}
+
+/// Heap entry ordered by distance so the farthest neighbour is on top.
+struct Neighbor<T> {
+    distance: T,
+    index: usize,
+}
+
+impl<T: RealField> PartialEq for Neighbor<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: RealField> Eq for Neighbor<T> {}
+
+impl<T: RealField> PartialOrd for Neighbor<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: RealField> Ord for Neighbor<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        total_cmp(&self.distance, &other.distance).then(self.index.cmp(&other.index))
+    }
+}
+
+impl<T: RealField, const D: usize> KdTree<T, D> {
+    pub fn build(points: Vec<Point<T, D>>) -> Self {
+        let mut tree = Self {
+            nodes: Vec::with_capacity(points.len()),
+            points,
+        };
+        let mut order: Vec<usize> = (0..tree.points.len()).collect();
+        let cell: BoundingSpaceN<T, D> = tree.points.iter().collect();
+        tree.build_node(&mut order, cell);
+        tree
+    }
+
+    fn build_node(&mut self, order: &mut [usize], cell: BoundingSpaceN<T, D>) -> Option<usize> {
+        if order.is_empty() {
+            return None;
+        }
+
+        let axis = cell.longest_axis();
+        let mid = order.len() / 2;
+        let points = &self.points;
+        order.select_nth_unstable_by(mid, |&a, &b| total_cmp(&points[a][axis], &points[b][axis]));
+
+        let point = order[mid];
+        let split = self.points[point][axis].to_owned();
+        let (mut left_cell, mut right_cell) = (cell.to_owned(), cell.to_owned());
+        left_cell.upper[axis] = split.to_owned();
+        right_cell.lower[axis] = split;
+
+        let index = self.nodes.len();
+        self.nodes.push(KdNode {
+            point,
+            axis,
+            cell,
+            left: None,
+            right: None,
+        });
+
+        let (lower, rest) = order.split_at_mut(mid);
+        let left = self.build_node(lower, left_cell);
+        let right = self.build_node(&mut rest[1..], right_cell);
+        self.nodes[index].left = left;
+        self.nodes[index].right = right;
+
+        Some(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn points(&self) -> &[Point<T, D>] {
+        &self.points
+    }
+
+    /// Bounds of the whole point set.
+    pub fn bound(&self) -> BoundingSpaceN<T, D> {
+        self.nodes
+            .first()
+            .map(|node| node.cell.to_owned())
+            .unwrap_or_else(BoundingSpaceN::empty)
+    }
+
+    /// Up to `k` nearest points as `(index, squared distance)`, closest first.
+    pub fn k_nearest(&self, point: &Point<T, D>, k: usize) -> Vec<(usize, T)> {
+        self.search_nearest(point, k, T::one())
+    }
+
+    pub fn nearest(&self, point: &Point<T, D>) -> Option<(usize, T)> {
+        self.k_nearest(point, 1).pop()
+    }
+
+    /// A point within a factor `1 + epsilon` of the nearest distance, found by
+    /// pruning cells that cannot improve the current best by that factor.
+    pub fn nearest_approx(&self, point: &Point<T, D>, epsilon: T) -> Option<(usize, T)> {
+        let factor = T::one() + epsilon;
+        self.search_nearest(point, 1, factor.to_owned() * factor)
+            .pop()
+    }
+
+    fn search_nearest(&self, point: &Point<T, D>, k: usize, factor: T) -> Vec<(usize, T)> {
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        if k > 0 && !self.nodes.is_empty() {
+            self.visit_nearest(0, point, k, &factor, &mut heap);
+        }
+
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|n| (n.index, n.distance))
+            .collect()
+    }
+
+    fn visit_nearest(
+        &self,
+        node: usize,
+        point: &Point<T, D>,
+        k: usize,
+        factor: &T,
+        heap: &mut BinaryHeap<Neighbor<T>>,
+    ) {
+        let KdNode {
+            point: index,
+            axis,
+            ref cell,
+            left,
+            right,
+        } = self.nodes[node];
+
+        if heap.len() == k {
+            let worst = heap.peek().expect("full heap").distance.to_owned();
+            if cell.distance_squared_to_point(point) * factor.to_owned() >= worst {
+                return;
+            }
+        }
+
+        let distance = (&self.points[index] - point).norm_squared();
+        if heap.len() < k {
+            heap.push(Neighbor { distance, index });
+        } else if distance < heap.peek().expect("full heap").distance {
+            heap.pop();
+            heap.push(Neighbor { distance, index });
+        }
+
+        let (near, far) = if point[axis] < self.points[index][axis] {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        for child in [near, far].into_iter().flatten() {
+            self.visit_nearest(child, point, k, factor, heap);
+        }
+    }
+
+    /// Indices of points within `radius` of `point`, in no particular order.
+    pub fn within_radius(&self, point: &Point<T, D>, radius: T) -> Vec<usize> {
+        let radius_squared = radius.to_owned() * radius;
+        self.collect(
+            |cell| cell.distance_squared_to_point(point) <= radius_squared,
+            |p| (p - point).norm_squared() <= radius_squared,
+        )
+    }
+
+    /// Indices of points inside the window, in no particular order.
+    pub fn within_bound(&self, window: &BoundingSpaceN<T, D>) -> Vec<usize> {
+        self.collect(|cell| cell.intersects(window), |p| window.contains(p))
+    }
+
+    fn collect(
+        &self,
+        descend: impl Fn(&BoundingSpaceN<T, D>) -> bool,
+        test: impl Fn(&Point<T, D>) -> bool,
+    ) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if !descend(&node.cell) {
+                continue;
+            }
+
+            if test(&self.points[node.point]) {
+                found.push(node.point);
+            }
+            stack.extend(node.left);
+            stack.extend(node.right);
+        }
+
+        found
+    }
+}
+
+impl<T: RealField, const D: usize> FromIterator<Point<T, D>> for KdTree<T, D> {
+    fn from_iter<I: IntoIterator<Item = Point<T, D>>>(points: I) -> Self {
+        Self::build(points.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3};
+
+    use super::*;
+
+    fn cloud() -> Vec<Point3<f64>> {
+        (0..500)
+            .map(|i| {
+                let t = i as f64;
+                Point3::new(
+                    (t * 0.37).sin() * 10.0,
+                    (t * 0.11).cos() * 7.0,
+                    (t * 0.53).sin() * 3.0,
+                )
+            })
+            .collect()
+    }
+
+    fn brute_force(points: &[Point3<f64>], query: &Point3<f64>) -> Vec<(usize, f64)> {
+        let mut all: Vec<_> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, (p - query).norm_squared()))
+            .collect();
+        all.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        all
+    }
+
+    #[test]
+    fn nearest_queries() {
+        let points = cloud();
+        let tree: KdTree<f64, 3> = points.iter().cloned().collect();
+        let query = Point3::new(1.0, -2.0, 0.5);
+        let expected = brute_force(&points, &query);
+
+        let nearest = tree.k_nearest(&query, 7);
+        assert_eq!(nearest.len(), 7);
+        for ((_, distance), (_, expected)) in nearest.iter().zip(&expected) {
+            assert_relative_eq!(*distance, *expected);
+        }
+        assert_eq!(tree.nearest(&query).unwrap().0, expected[0].0);
+
+        let (_, approx) = tree.nearest_approx(&query, 0.5).unwrap();
+        assert!(approx <= expected[0].1 * 1.5 * 1.5);
+    }
+
+    #[test]
+    fn range_queries() {
+        let points = cloud();
+        let tree = KdTree::build(points.clone());
+        let query = Point3::new(0.0, 0.0, 0.0);
+
+        let mut found = tree.within_radius(&query, 2.5);
+        found.sort();
+        let mut expected: Vec<usize> = brute_force(&points, &query)
+            .into_iter()
+            .filter(|(_, d)| *d <= 6.25)
+            .map(|(i, _)| i)
+            .collect();
+        expected.sort();
+        assert_eq!(found, expected);
+
+        let window = BoundingSpaceN::new(Point3::new(-2.0, 0.0, -1.0), Point3::new(5.0, 3.0, 1.0));
+        let mut found = tree.within_bound(&window);
+        found.sort();
+        let expected: Vec<usize> = (0..points.len())
+            .filter(|&i| window.contains(&points[i]))
+            .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn empty_and_degenerate() {
+        let empty = KdTree::<f64, 2>::build(Vec::new());
+        assert!(empty.nearest(&Point2::origin()).is_none());
+        assert!(empty.bound().is_empty());
+
+        let same = KdTree::build(vec![Point2::new(1.0, 1.0); 10]);
+        assert_eq!(same.k_nearest(&Point2::origin(), 3).len(), 3);
+        assert_eq!(same.within_radius(&Point2::new(1.0, 1.0), 0.0).len(), 10);
+    }
+}
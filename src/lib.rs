@@ -1,7 +1,40 @@
 pub extern crate nalgebra;
 
+use core::borrow::Borrow;
+
 use nalgebra::{SVector, RealField, Point};
 
+pub mod bvh;
+mod capsule;
+pub mod convex_hull;
+pub mod curve;
+mod distance;
+pub mod dynamic_tree;
+mod ellipsoid;
+pub mod grid;
+pub mod iou;
+mod kdop;
+pub mod kdtree;
+mod linalg;
+pub mod nms;
+mod normalize;
+mod obb;
+pub mod orthtree;
+mod ray;
+pub mod rtree;
+mod sphere;
+pub mod sweep;
+mod transform;
+
+pub use capsule::{BoundingCapsule2, BoundingCapsule3, BoundingCapsuleN};
+pub use ellipsoid::{BoundingEllipse, BoundingEllipsoid, BoundingEllipsoidN};
+pub use kdop::{Dop14, Dop18, Dop26, Dop8, KDop};
+pub use normalize::UnitCubeMap;
+pub use obb::{OrientedBoundingSpace2, OrientedBoundingSpace3, OrientedBoundingSpaceN};
+pub use ray::{PrecomputedRay, Ray};
+pub use sphere::{BoundingCircle, BoundingSphere, BoundingSphereN};
+pub use transform::TransformBounds;
+
 pub type BoundingSpace1<T> = BoundingSpaceN<T, 1>;
 pub type BoundingSpace2<T> = BoundingSpaceN<T, 2>;
 pub type BoundingSpace3<T> = BoundingSpaceN<T, 3>;
@@ -10,6 +43,20 @@ pub type BoundingRange<T> = BoundingSpace1<T>;
 pub type BoundingSquare<T> = BoundingSpace2<T>;
 pub type BoundingBox<T> = BoundingSpace3<T>;
 
+pub(crate) fn max_value<T: RealField>() -> T {
+    T::max_value().expect("real field must be bounded")
+}
+
+pub(crate) fn is_nan<T: RealField>(value: &T) -> bool {
+    value.partial_cmp(value).is_none()
+}
+
+/// Total order for sorting that places NaN above every number, so that
+/// `sort_by` never sees an inconsistent comparator.
+pub(crate) fn total_cmp<T: RealField>(a: &T, b: &T) -> core::cmp::Ordering {
+    a.partial_cmp(b).unwrap_or_else(|| is_nan(a).cmp(&is_nan(b)))
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct BoundingSpaceN<T: RealField, const D: usize> {
     pub lower: Point<T, D>,
@@ -40,10 +87,100 @@ impl<T: RealField, const D: usize> BoundingSpaceN<T, D> {
         }
     }
 
+    /// Space containing no points: `lower` is `+max` and `upper` is `-max`
+    /// on every axis, so the first `expand` collapses it onto the point.
+    pub fn empty() -> Self {
+        let max = max_value::<T>();
+        Self::from_values(max.to_owned(), -max)
+    }
+
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Point<T, D>>,
+    {
+        let mut points = points.into_iter();
+        let mut bound = Self::from_point(points.next()?.borrow().to_owned());
+        for point in points {
+            bound.expand(point.borrow());
+        }
+        Some(bound)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lower.coords.iter().zip(&self.upper.coords).any(|(l, u)| l > u)
+    }
+
     pub fn diagonal(&self) -> SVector<T, D> {
+        if self.is_empty() {
+            return SVector::zeros();
+        }
+
         &self.upper - &self.lower
     }
 
+    /// Midpoint of the space. An empty space has no midpoint and gives the
+    /// origin, so check `is_empty` before building a volume around it.
+    pub fn center(&self) -> Point<T, D> {
+        ((&self.lower.coords + &self.upper.coords) / (T::one() + T::one())).into()
+    }
+
+    /// Half of `diagonal`, so zero for an empty space.
+    pub fn half_extents(&self) -> SVector<T, D> {
+        self.diagonal() / (T::one() + T::one())
+    }
+
+    pub fn volume(&self) -> T {
+        self.diagonal().iter().fold(T::one(), |acc, e| acc * e.to_owned())
+    }
+
+    /// Measure of the boundary: perimeter in 2D, surface area in 3D,
+    /// and the number of end points in 1D.
+    pub fn surface_measure(&self) -> T {
+        if self.is_empty() {
+            return T::zero();
+        }
+
+        let diagonal = self.diagonal();
+        let faces = (0..D).fold(T::zero(), |acc, i| {
+            let face = diagonal
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(T::one(), |acc, (_, e)| acc * e.to_owned());
+            acc + face
+        });
+
+        faces * (T::one() + T::one())
+    }
+
+    pub fn longest_axis(&self) -> usize {
+        let diagonal = self.diagonal();
+        (0..D).fold(0, |best, i| if diagonal[i] > diagonal[best] { i } else { best })
+    }
+
+    pub fn shortest_axis(&self) -> usize {
+        let diagonal = self.diagonal();
+        (0..D).fold(0, |best, i| if diagonal[i] < diagonal[best] { i } else { best })
+    }
+
+    /// Ratio of the longest extent to the shortest one, `None` when the
+    /// space is empty, has no axes or the shortest extent is zero.
+    pub fn aspect_ratio(&self) -> Option<T> {
+        if D == 0 || self.is_empty() {
+            return None;
+        }
+
+        let diagonal = self.diagonal();
+        let shortest = diagonal[self.shortest_axis()].to_owned();
+
+        if shortest.is_zero() {
+            None
+        } else {
+            Some(diagonal[self.longest_axis()].to_owned() / shortest)
+        }
+    }
+
     pub fn contains(&self, point: &Point<T, D>) -> bool {
         for (l, p) in self.lower.coords.iter().zip(&point.coords) {
             if l > p {
@@ -60,6 +197,59 @@ impl<T: RealField, const D: usize> BoundingSpaceN<T, D> {
         true
     }
 
+    pub fn contains_strict(&self, point: &Point<T, D>) -> bool {
+        self.lower.coords.iter().zip(&point.coords).all(|(l, p)| l < p)
+            && self.upper.coords.iter().zip(&point.coords).all(|(u, p)| u > p)
+    }
+
+    pub fn contains_space(&self, other: &Self) -> bool {
+        self.lower.coords.iter().zip(&other.lower.coords).all(|(l, o)| l <= o)
+            && self.upper.coords.iter().zip(&other.upper.coords).all(|(u, o)| u >= o)
+    }
+
+    pub fn contains_space_strict(&self, other: &Self) -> bool {
+        if other.is_empty() {
+            return true;
+        }
+
+        self.lower.coords.iter().zip(&other.lower.coords).all(|(l, o)| l < o)
+            && self.upper.coords.iter().zip(&other.upper.coords).all(|(u, o)| u > o)
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.lower.coords.iter().zip(&other.upper.coords).all(|(l, u)| l <= u)
+            && other.lower.coords.iter().zip(&self.upper.coords).all(|(l, u)| l <= u)
+    }
+
+    pub fn intersects_strict(&self, other: &Self) -> bool {
+        self.lower.coords.iter().zip(&other.upper.coords).all(|(l, u)| l < u)
+            && other.lower.coords.iter().zip(&self.upper.coords).all(|(l, u)| l < u)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut bound = self.to_owned();
+        bound.merge(other);
+        bound
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.expand_lower(&other.lower);
+        self.expand_upper(&other.upper);
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let bound = Self {
+            lower: self.lower.coords.zip_map(&other.lower.coords, |a, b| a.max(b)).into(),
+            upper: self.upper.coords.zip_map(&other.upper.coords, |a, b| a.min(b)).into(),
+        };
+
+        if bound.is_empty() {
+            None
+        } else {
+            Some(bound)
+        }
+    }
+
     pub fn expand_lower(&mut self, point: &Point<T, D>) {
         self.lower.coords.zip_apply(&point.coords, |l, p| {
             *l = p.min(l.to_owned());
@@ -88,10 +278,42 @@ impl<T: RealField, const D: usize> Default for BoundingSpaceN<T, D>
     }
 }
 
+impl<T: RealField, const D: usize> Extend<Point<T, D>> for BoundingSpaceN<T, D> {
+    fn extend<I: IntoIterator<Item = Point<T, D>>>(&mut self, points: I) {
+        for point in points {
+            self.expand(&point);
+        }
+    }
+}
+
+impl<'a, T: RealField, const D: usize> Extend<&'a Point<T, D>> for BoundingSpaceN<T, D> {
+    fn extend<I: IntoIterator<Item = &'a Point<T, D>>>(&mut self, points: I) {
+        for point in points {
+            self.expand(point);
+        }
+    }
+}
+
+impl<T: RealField, const D: usize> FromIterator<Point<T, D>> for BoundingSpaceN<T, D> {
+    fn from_iter<I: IntoIterator<Item = Point<T, D>>>(points: I) -> Self {
+        let mut bound = Self::empty();
+        bound.extend(points);
+        bound
+    }
+}
+
+impl<'a, T: RealField, const D: usize> FromIterator<&'a Point<T, D>> for BoundingSpaceN<T, D> {
+    fn from_iter<I: IntoIterator<Item = &'a Point<T, D>>>(points: I) -> Self {
+        let mut bound = Self::empty();
+        bound.extend(points);
+        bound
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use approx::assert_relative_eq;
-    use nalgebra::Point1;
+    use nalgebra::{Point1, Point2, Point3, Vector2, Vector3};
 
     use super::*;
 
@@ -115,6 +337,113 @@ mod tests {
         assert_relative_eq!(bound.upper.x, point.x);
     }
 
+    #[test]
+    fn empty_space() {
+        let mut bound = BoundingSpaceN::<f64, 2>::empty();
+
+        assert!(bound.is_empty());
+        assert!(!bound.contains(&Point2::origin()));
+        assert_relative_eq!(bound.diagonal(), Vector2::zeros());
+
+        let point = Point2::new(1.0, -2.0);
+        bound.expand(&point);
+
+        assert!(!bound.is_empty());
+        assert!(bound.contains(&point));
+        assert!(!bound.contains(&Point2::origin()));
+        assert_relative_eq!(bound.lower, point);
+        assert_relative_eq!(bound.upper, point);
+    }
+
+    #[test]
+    fn collect_points() {
+        let points = [
+            Point2::new(1.0, 0.0),
+            Point2::new(-1.0, 2.0),
+            Point2::new(0.5, -3.0),
+        ];
+
+        let bound: BoundingSpaceN<f64, 2> = points.iter().collect();
+        assert_relative_eq!(bound.lower, Point2::new(-1.0, -3.0));
+        assert_relative_eq!(bound.upper, Point2::new(1.0, 2.0));
+
+        let owned: BoundingSpaceN<f64, 2> = points.into_iter().collect();
+        assert_relative_eq!(owned.lower, bound.lower);
+        assert_relative_eq!(owned.upper, bound.upper);
+
+        let from_points = BoundingSpaceN::from_points(points.iter()).unwrap();
+        assert_relative_eq!(from_points.lower, bound.lower);
+        assert_relative_eq!(from_points.upper, bound.upper);
+
+        assert!(BoundingSpaceN::<f64, 2>::from_points(Vec::<Point2<f64>>::new()).is_none());
+        assert!(std::iter::empty::<Point2<f64>>().collect::<BoundingSpaceN<_, 2>>().is_empty());
+    }
+
+    #[test]
+    fn union_and_intersection() {
+        let a = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(2.0, 2.0));
+        let b = BoundingSpaceN::new(Point2::new(1.0, -1.0), Point2::new(3.0, 1.0));
+        let c = BoundingSpaceN::new(Point2::new(2.0, 3.0), Point2::new(4.0, 4.0));
+        let empty = BoundingSpaceN::<f64, 2>::empty();
+
+        let union = a.union(&b);
+        assert_relative_eq!(union.lower, Point2::new(0.0, -1.0));
+        assert_relative_eq!(union.upper, Point2::new(3.0, 2.0));
+        assert!(union.contains_space(&a) && union.contains_space(&b));
+        assert!(!union.contains_space_strict(&a));
+
+        let intersection = a.intersection(&b).unwrap();
+        assert_relative_eq!(intersection.lower, Point2::new(1.0, 0.0));
+        assert_relative_eq!(intersection.upper, Point2::new(2.0, 1.0));
+        assert!(a.intersects_strict(&b));
+
+        assert!(a.intersection(&c).is_none());
+        assert!(!a.intersects(&c));
+
+        let touching = BoundingSpaceN::new(Point2::new(2.0, 1.0), Point2::new(3.0, 3.0));
+        assert!(a.intersects(&touching));
+        assert!(!a.intersects_strict(&touching));
+
+        let mut merged = empty;
+        merged.merge(&a);
+        assert_relative_eq!(merged.lower, a.lower);
+        assert_relative_eq!(merged.upper, a.upper);
+        assert_relative_eq!(a.union(&empty).upper, a.upper);
+
+        assert!(!a.intersects(&empty));
+        assert!(a.intersection(&empty).is_none());
+        assert!(a.contains_space(&empty) && a.contains_space_strict(&empty));
+        assert!(!empty.contains_space(&a));
+    }
+
+    #[test]
+    fn measures() {
+        let bound = BoundingSpaceN::new(Point3::new(-1.0, 0.0, 1.0), Point3::new(1.0, 4.0, 2.0));
+
+        assert_relative_eq!(bound.center(), Point3::new(0.0, 2.0, 1.5));
+        assert_relative_eq!(bound.half_extents(), Vector3::new(1.0, 2.0, 0.5));
+        assert_relative_eq!(bound.volume(), 8.0);
+        assert_relative_eq!(bound.surface_measure(), 2.0 * (8.0 + 2.0 + 4.0));
+        assert_eq!(bound.longest_axis(), 1);
+        assert_eq!(bound.shortest_axis(), 2);
+        assert_relative_eq!(bound.aspect_ratio().unwrap(), 4.0);
+
+        let square = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(2.0, 3.0));
+        assert_relative_eq!(square.surface_measure(), 10.0);
+
+        let flat = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(2.0, 0.0));
+        assert_relative_eq!(flat.volume(), 0.0);
+        assert!(flat.aspect_ratio().is_none());
+
+        let empty = BoundingSpaceN::<f64, 3>::empty();
+        assert_relative_eq!(empty.volume(), 0.0);
+        assert_relative_eq!(empty.surface_measure(), 0.0);
+        assert!(empty.aspect_ratio().is_none());
+        assert!(BoundingSpaceN::<f64, 0>::new(Point::origin(), Point::origin())
+            .aspect_ratio()
+            .is_none());
+    }
+
     #[test]
     fn expand_1d() {
         let value = 0.0_f64;
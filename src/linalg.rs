@@ -0,0 +1,222 @@
+//! Dense routines nalgebra cannot provide for a generic `const D`. Its
+//! `determinant` and `SymmetricEigen` need `Const<D>: DimMin<Const<D>>` and
+//! `DimSub<U1>`, which only concrete sizes satisfy, and its dynamically
+//! sized matrices need the `alloc` feature this crate does not enable.
+//! Inverses of `SMatrix` have no such bounds and use `try_inverse` directly.
+
+use nalgebra::{convert, Point, RealField, SMatrix, SVector};
+
+use crate::total_cmp;
+
+/// Solves `a * x = b` by Gaussian elimination with partial pivoting, `None`
+/// when the system is numerically singular. Stored as rows since the size
+/// is only known at run time.
+pub(crate) fn solve<T: RealField>(mut a: Vec<Vec<T>>, mut b: Vec<T>) -> Option<Vec<T>> {
+    let n = b.len();
+    let scale = a
+        .iter()
+        .flatten()
+        .fold(T::zero(), |acc, v| acc.max(v.to_owned().abs()));
+    let tolerance = scale * T::default_epsilon() * convert(n.max(1) as f64 * 16.0);
+
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|&i, &j| {
+                a[i][column]
+                    .to_owned()
+                    .abs()
+                    .partial_cmp(&a[j][column].to_owned().abs())
+                    .unwrap_or(core::cmp::Ordering::Equal)
+            })
+            .expect("non-empty range");
+        if a[pivot][column].to_owned().abs() <= tolerance {
+            return None;
+        }
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+
+        for row in column + 1..n {
+            let (upper, lower) = a.split_at_mut(row);
+            let (pivot_row, target) = (&upper[column], &mut lower[0]);
+            let factor = target[column].to_owned() / pivot_row[column].to_owned();
+            for (t, p) in target[column..].iter_mut().zip(&pivot_row[column..]) {
+                *t -= factor.to_owned() * p.to_owned();
+            }
+            let delta = factor * b[column].to_owned();
+            b[row] -= delta;
+        }
+    }
+
+    let mut x = vec![T::zero(); n];
+    for row in (0..n).rev() {
+        let sum = (row + 1..n).fold(b[row].to_owned(), |acc, k| {
+            acc - a[row][k].to_owned() * x[k].to_owned()
+        });
+        x[row] = sum / a[row][row].to_owned();
+    }
+
+    Some(x)
+}
+
+/// Determinant by Gaussian elimination with partial pivoting.
+pub(crate) fn determinant<T: RealField, const D: usize>(matrix: &SMatrix<T, D, D>) -> T {
+    let mut a = matrix.to_owned();
+    let mut determinant = T::one();
+
+    for column in 0..D {
+        let pivot = (column..D)
+            .max_by(|&i, &j| {
+                a[(i, column)]
+                    .to_owned()
+                    .abs()
+                    .partial_cmp(&a[(j, column)].to_owned().abs())
+                    .unwrap_or(core::cmp::Ordering::Equal)
+            })
+            .expect("non-empty range");
+        if a[(pivot, column)].is_zero() {
+            return T::zero();
+        }
+        if pivot != column {
+            a.swap_rows(pivot, column);
+            determinant = -determinant;
+        }
+
+        determinant *= a[(column, column)].to_owned();
+        for row in column + 1..D {
+            let factor = a[(row, column)].to_owned() / a[(column, column)].to_owned();
+            for k in column..D {
+                let delta = factor.to_owned() * a[(column, k)].to_owned();
+                a[(row, k)] -= delta;
+            }
+        }
+    }
+
+    determinant
+}
+
+/// Mean and covariance of a point set.
+pub(crate) fn covariance<T: RealField, const D: usize>(
+    points: &[Point<T, D>],
+) -> (Point<T, D>, SMatrix<T, D, D>) {
+    let count: T = convert(points.len().max(1) as f64);
+    let mean = points
+        .iter()
+        .fold(SVector::<T, D>::zeros(), |acc, p| acc + &p.coords)
+        / count.to_owned();
+
+    let covariance = points.iter().fold(SMatrix::<T, D, D>::zeros(), |acc, p| {
+        let delta = &p.coords - &mean;
+        acc + &delta * delta.transpose()
+    }) / count;
+
+    (mean.into(), covariance)
+}
+
+/// Eigen decomposition of a symmetric matrix by cyclic Jacobi rotations.
+/// Eigenvalues are sorted in decreasing order and the eigenvectors are the
+/// columns of the returned matrix, forming a proper rotation.
+pub(crate) fn symmetric_eigen<T: RealField, const D: usize>(
+    matrix: &SMatrix<T, D, D>,
+) -> (SVector<T, D>, SMatrix<T, D, D>) {
+    let mut a = matrix.to_owned();
+    let mut v = SMatrix::<T, D, D>::identity();
+    let two = T::one() + T::one();
+    let tolerance = T::default_epsilon() * T::default_epsilon() * a.norm_squared();
+
+    for _ in 0..64 {
+        let off_diagonal = (0..D)
+            .flat_map(|p| (0..D).filter(move |&q| q != p).map(move |q| (p, q)))
+            .fold(T::zero(), |acc, (p, q)| {
+                acc + a[(p, q)].to_owned() * a[(p, q)].to_owned()
+            });
+        if off_diagonal <= tolerance {
+            break;
+        }
+
+        for p in 0..D {
+            for q in p + 1..D {
+                if a[(p, q)].is_zero() {
+                    continue;
+                }
+
+                let theta = (a[(q, q)].to_owned() - a[(p, p)].to_owned())
+                    / (two.to_owned() * a[(p, q)].to_owned());
+                let t = T::one()
+                    / (theta.to_owned().abs()
+                        + (theta.to_owned() * theta.to_owned() + T::one()).sqrt());
+                let t = if theta < T::zero() { -t } else { t };
+                let c = T::one() / (t.to_owned() * t.to_owned() + T::one()).sqrt();
+                let s = t * c.to_owned();
+
+                for k in 0..D {
+                    let (kp, kq) = (a[(k, p)].to_owned(), a[(k, q)].to_owned());
+                    a[(k, p)] = c.to_owned() * kp.to_owned() - s.to_owned() * kq.to_owned();
+                    a[(k, q)] = s.to_owned() * kp + c.to_owned() * kq;
+                }
+                for k in 0..D {
+                    let (pk, qk) = (a[(p, k)].to_owned(), a[(q, k)].to_owned());
+                    a[(p, k)] = c.to_owned() * pk.to_owned() - s.to_owned() * qk.to_owned();
+                    a[(q, k)] = s.to_owned() * pk + c.to_owned() * qk;
+                }
+                for k in 0..D {
+                    let (kp, kq) = (v[(k, p)].to_owned(), v[(k, q)].to_owned());
+                    v[(k, p)] = c.to_owned() * kp.to_owned() - s.to_owned() * kq.to_owned();
+                    v[(k, q)] = s.to_owned() * kp + c.to_owned() * kq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..D).collect();
+    order.sort_by(|&i, &j| total_cmp(&a[(j, j)], &a[(i, i)]));
+
+    let values = SVector::<T, D>::from_fn(|i, _| a[(order[i], order[i])].to_owned());
+    let mut vectors = SMatrix::<T, D, D>::from_fn(|r, c| v[(r, order[c])].to_owned());
+    if D > 0 && determinant(&vectors) < T::zero() {
+        let mut last = vectors.column_mut(D - 1);
+        last.neg_mut();
+    }
+
+    (values, vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Matrix3, Rotation3, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn solve_and_determinant() {
+        let a = vec![
+            vec![2.0, 1.0, -1.0],
+            vec![-3.0, -1.0, 2.0],
+            vec![-2.0, 1.0, 2.0],
+        ];
+        let x = solve(a, vec![8.0, -11.0, -3.0]).unwrap();
+        assert_relative_eq!(x[0], 2.0, epsilon = 1e-12);
+        assert_relative_eq!(x[1], 3.0, epsilon = 1e-12);
+        assert_relative_eq!(x[2], -1.0, epsilon = 1e-12);
+        assert!(solve(vec![vec![1.0, 2.0], vec![2.0, 4.0]], vec![1.0, 2.0]).is_none());
+
+        let m = Matrix3::new(2.0, 1.0, -1.0, -3.0, -1.0, 2.0, -2.0, 1.0, 2.0);
+        assert_relative_eq!(determinant(&m), m.determinant(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn jacobi_eigen() {
+        let rotation = Rotation3::from_euler_angles(0.3, -0.7, 1.1).into_inner();
+        let matrix =
+            rotation * Matrix3::from_diagonal(&Vector3::new(1.0, 5.0, 3.0)) * rotation.transpose();
+
+        let (values, vectors) = symmetric_eigen(&matrix);
+        assert_relative_eq!(values, Vector3::new(5.0, 3.0, 1.0), epsilon = 1e-10);
+        assert_relative_eq!(determinant(&vectors), 1.0, epsilon = 1e-10);
+        assert_relative_eq!(
+            vectors * Matrix3::from_diagonal(&values) * vectors.transpose(),
+            matrix,
+            epsilon = 1e-10
+        );
+    }
+}
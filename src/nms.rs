@@ -0,0 +1,291 @@
+//! Post-processing of scored detections.
+//!
+//! All functions take parallel slices of boxes and scores and panic when
+//! their lengths differ. Returned indices are ordered by descending score.
+//! Boxes with a NaN score are discarded.
+
+use nalgebra::{convert, RealField, SVector};
+
+use crate::{is_nan, total_cmp, BoundingSpaceN};
+
+#[derive(Debug, Clone, Copy)]
+pub enum SoftNmsDecay<T> {
+    /// Scores of boxes overlapping above `iou_threshold` are scaled by `1 - iou`.
+    Linear { iou_threshold: T },
+    /// Scores are scaled by `exp(-iou^2 / sigma)`.
+    Gaussian { sigma: T },
+}
+
+fn order_by_score<T: RealField>(scores: &[T]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..scores.len()).filter(|&i| !is_nan(&scores[i])).collect();
+    order.sort_by(|&a, &b| total_cmp(&scores[b], &scores[a]));
+    order
+}
+
+/// Greedy non-maximum suppression: keeps a box unless it overlaps an
+/// already kept box with IoU above `iou_threshold`.
+pub fn nms<T: RealField, const D: usize>(
+    boxes: &[BoundingSpaceN<T, D>],
+    scores: &[T],
+    iou_threshold: T,
+) -> Vec<usize> {
+    assert_eq!(
+        boxes.len(),
+        scores.len(),
+        "boxes and scores must have equal length"
+    );
+
+    let mut keep: Vec<usize> = Vec::new();
+
+    for i in order_by_score(scores) {
+        if keep
+            .iter()
+            .all(|&k| boxes[k].iou(&boxes[i]) <= iou_threshold)
+        {
+            keep.push(i);
+        }
+    }
+
+    keep
+}
+
+/// Greedy NMS applied independently to every class.
+pub fn batched_nms<T: RealField, const D: usize>(
+    boxes: &[BoundingSpaceN<T, D>],
+    scores: &[T],
+    classes: &[usize],
+    iou_threshold: T,
+) -> Vec<usize> {
+    assert_eq!(
+        boxes.len(),
+        scores.len(),
+        "boxes and scores must have equal length"
+    );
+    assert_eq!(
+        boxes.len(),
+        classes.len(),
+        "boxes and classes must have equal length"
+    );
+
+    let mut keep: Vec<usize> = Vec::new();
+
+    for i in order_by_score(scores) {
+        if keep
+            .iter()
+            .filter(|&&k| classes[k] == classes[i])
+            .all(|&k| boxes[k].iou(&boxes[i]) <= iou_threshold)
+        {
+            keep.push(i);
+        }
+    }
+
+    keep
+}
+
+/// Soft-NMS of Bodla et al. Returns the surviving indices with their decayed
+/// scores, dropping boxes whose score falls below `score_threshold`.
+pub fn soft_nms<T: RealField, const D: usize>(
+    boxes: &[BoundingSpaceN<T, D>],
+    scores: &[T],
+    decay: SoftNmsDecay<T>,
+    score_threshold: T,
+) -> Vec<(usize, T)> {
+    assert_eq!(
+        boxes.len(),
+        scores.len(),
+        "boxes and scores must have equal length"
+    );
+
+    let mut remaining: Vec<(usize, T)> = scores
+        .iter()
+        .cloned()
+        .enumerate()
+        .filter(|(_, score)| !is_nan(score))
+        .collect();
+    let mut keep = Vec::new();
+
+    while let Some(best) = remaining
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| total_cmp(&a.1, &b.1))
+        .map(|(position, _)| position)
+    {
+        let (index, score) = remaining.swap_remove(best);
+        if score < score_threshold {
+            break;
+        }
+
+        for (other, other_score) in remaining.iter_mut() {
+            let iou = boxes[index].iou(&boxes[*other]);
+            let weight = match &decay {
+                SoftNmsDecay::Linear { iou_threshold } if iou > *iou_threshold => T::one() - iou,
+                SoftNmsDecay::Linear { .. } => T::one(),
+                SoftNmsDecay::Gaussian { sigma } => {
+                    (-(iou.to_owned() * iou) / sigma.to_owned()).exp()
+                }
+            };
+            *other_score *= weight;
+        }
+
+        keep.push((index, score));
+    }
+
+    keep
+}
+
+/// Weighted box fusion of Solovyev et al. for a single model: boxes matching
+/// a cluster with IoU above `iou_threshold` are merged into its
+/// score-weighted average box, and the cluster scores the mean of its members.
+pub fn weighted_box_fusion<T: RealField, const D: usize>(
+    boxes: &[BoundingSpaceN<T, D>],
+    scores: &[T],
+    iou_threshold: T,
+) -> Vec<(BoundingSpaceN<T, D>, T)> {
+    assert_eq!(
+        boxes.len(),
+        scores.len(),
+        "boxes and scores must have equal length"
+    );
+
+    struct Cluster<T: RealField, const D: usize> {
+        fused: BoundingSpaceN<T, D>,
+        members: Vec<usize>,
+    }
+
+    let mut clusters: Vec<Cluster<T, D>> = Vec::new();
+
+    for i in order_by_score(scores) {
+        let matched = clusters
+            .iter()
+            .enumerate()
+            .map(|(c, cluster)| (c, cluster.fused.iou(&boxes[i])))
+            .filter(|(_, iou)| *iou > iou_threshold)
+            .max_by(|a, b| total_cmp(&a.1, &b.1))
+            .map(|(c, _)| c);
+
+        match matched {
+            Some(c) => {
+                let cluster = &mut clusters[c];
+                cluster.members.push(i);
+
+                let total = cluster
+                    .members
+                    .iter()
+                    .fold(T::zero(), |acc, &m| acc + scores[m].to_owned());
+                let (lower, upper) = cluster.members.iter().fold(
+                    (SVector::<T, D>::zeros(), SVector::<T, D>::zeros()),
+                    |(lower, upper), &m| {
+                        (
+                            lower + &boxes[m].lower.coords * scores[m].to_owned(),
+                            upper + &boxes[m].upper.coords * scores[m].to_owned(),
+                        )
+                    },
+                );
+
+                cluster.fused =
+                    BoundingSpaceN::new((lower / total.to_owned()).into(), (upper / total).into());
+            }
+            None => clusters.push(Cluster {
+                fused: boxes[i].to_owned(),
+                members: vec![i],
+            }),
+        }
+    }
+
+    clusters
+        .into_iter()
+        .map(|cluster| {
+            let count: T = convert(cluster.members.len() as f64);
+            let total = cluster
+                .members
+                .iter()
+                .fold(T::zero(), |acc, &m| acc + scores[m].to_owned());
+            (cluster.fused, total / count)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::Point2;
+
+    use super::*;
+    use crate::BoundingSquare;
+
+    fn square(x: f32, y: f32, size: f32) -> BoundingSquare<f32> {
+        BoundingSpaceN::new(Point2::new(x, y), Point2::new(x + size, y + size))
+    }
+
+    fn detections() -> (Vec<BoundingSquare<f32>>, Vec<f32>) {
+        let boxes = vec![
+            square(0.0, 0.0, 10.0),
+            square(1.0, 0.0, 10.0),
+            square(20.0, 20.0, 10.0),
+            square(0.0, 1.0, 10.0),
+        ];
+        (boxes, vec![0.8, 0.9, 0.7, 0.3])
+    }
+
+    #[test]
+    fn greedy_and_batched() {
+        let (boxes, scores) = detections();
+
+        assert_eq!(nms(&boxes, &scores, 0.5), vec![1, 2]);
+        assert_eq!(
+            batched_nms(&boxes, &scores, &[0, 1, 0, 1], 0.5),
+            vec![1, 0, 2]
+        );
+    }
+
+    #[test]
+    fn soft() {
+        let (boxes, scores) = detections();
+
+        let linear = soft_nms(
+            &boxes,
+            &scores,
+            SoftNmsDecay::Linear { iou_threshold: 0.5 },
+            0.1,
+        );
+        assert_eq!(linear[0], (1, 0.9));
+        assert_eq!(linear[1], (2, 0.7));
+        assert!(linear.iter().all(|(i, _)| *i != 3));
+
+        let gaussian = soft_nms(&boxes, &scores, SoftNmsDecay::Gaussian { sigma: 0.5 }, 0.0);
+        assert_eq!(gaussian.len(), 4);
+        let decayed = gaussian.iter().find(|(i, _)| *i == 0).unwrap().1;
+        assert!(decayed < 0.8 && decayed > 0.0);
+    }
+
+    #[test]
+    fn fusion() {
+        let (boxes, scores) = detections();
+        let fused = weighted_box_fusion(&boxes, &scores, 0.55);
+
+        assert_eq!(fused.len(), 2);
+        let (bound, score) = &fused[0];
+        assert_relative_eq!(bound.lower.x, 0.9 / 2.0, epsilon = 1e-6);
+        assert_relative_eq!(bound.lower.y, 0.3 / 2.0, epsilon = 1e-6);
+        assert_relative_eq!(*score, 2.0 / 3.0, epsilon = 1e-6);
+        assert_relative_eq!(fused[1].0.lower, boxes[2].lower);
+    }
+
+    #[test]
+    fn nan_scores_are_discarded() {
+        let (mut boxes, mut scores) = detections();
+        boxes.push(square(0.5, 0.5, 10.0));
+        scores.push(f32::NAN);
+        scores[2] = f32::NAN;
+
+        assert_eq!(nms(&boxes, &scores, 0.5), vec![1]);
+        assert_eq!(
+            batched_nms(&boxes, &scores, &[0, 1, 0, 1, 0], 0.5),
+            vec![1, 0]
+        );
+        let soft = soft_nms(&boxes, &scores, SoftNmsDecay::Gaussian { sigma: 0.5 }, 0.0);
+        assert_eq!(soft.len(), 3);
+        assert!(soft.iter().all(|(i, s)| *i != 2 && *i != 4 && !s.is_nan()));
+        assert_eq!(weighted_box_fusion(&boxes, &scores, 0.55).len(), 1);
+    }
+}
@@ -0,0 +1,243 @@
+use nalgebra::allocator::Allocator;
+use nalgebra::{
+    Const, DefaultAllocator, DimNameAdd, DimNameSum, OMatrix, Point, RealField, SVector, TAffine,
+    Transform, U1,
+};
+
+use crate::BoundingSpaceN;
+
+type Homogeneous<T, const D: usize> =
+    OMatrix<T, DimNameSum<Const<D>, U1>, DimNameSum<Const<D>, U1>>;
+
+/// Affine map between a space and the unit hypercube `[0, 1]^D`.
+///
+/// Axes with no width, including every axis of an empty space, are
+/// collapsed: points normalise to 0.5 and denormalise to the midpoint of
+/// the axis, and vectors normalise to zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitCubeMap<T: RealField, const D: usize> {
+    origin: Point<T, D>,
+    extents: SVector<T, D>,
+}
+
+impl<T: RealField, const D: usize> UnitCubeMap<T, D> {
+    pub fn new(bound: &BoundingSpaceN<T, D>) -> Self {
+        let mut origin = bound.lower.to_owned();
+        let mut extents = bound.diagonal();
+        let two = T::one() + T::one();
+
+        for i in 0..D {
+            if bound.is_empty() || bound.upper[i] <= bound.lower[i] {
+                origin[i] =
+                    (bound.lower[i].to_owned() + bound.upper[i].to_owned()) / two.to_owned();
+                extents[i] = T::zero();
+            }
+        }
+
+        Self { origin, extents }
+    }
+
+    /// Widths of the mapped axes, zero where collapsed.
+    pub fn extents(&self) -> &SVector<T, D> {
+        &self.extents
+    }
+
+    fn is_collapsed(&self, axis: usize) -> bool {
+        self.extents[axis] == T::zero()
+    }
+
+    pub fn normalize_point(&self, point: &Point<T, D>) -> Point<T, D> {
+        let half = T::one() / (T::one() + T::one());
+        Point::from(SVector::<T, D>::from_fn(|i, _| {
+            if self.is_collapsed(i) {
+                half.to_owned()
+            } else {
+                (point[i].to_owned() - self.origin[i].to_owned()) / self.extents[i].to_owned()
+            }
+        }))
+    }
+
+    pub fn denormalize_point(&self, point: &Point<T, D>) -> Point<T, D> {
+        &self.origin + self.extents.component_mul(&point.coords)
+    }
+
+    pub fn normalize_vector(&self, vector: &SVector<T, D>) -> SVector<T, D> {
+        SVector::<T, D>::from_fn(|i, _| {
+            if self.is_collapsed(i) {
+                T::zero()
+            } else {
+                vector[i].to_owned() / self.extents[i].to_owned()
+            }
+        })
+    }
+
+    pub fn denormalize_vector(&self, vector: &SVector<T, D>) -> SVector<T, D> {
+        self.extents.component_mul(vector)
+    }
+}
+
+impl<T: RealField, const D: usize> UnitCubeMap<T, D>
+where
+    Const<D>: DimNameAdd<U1>,
+    DefaultAllocator: Allocator<T, DimNameSum<Const<D>, U1>, DimNameSum<Const<D>, U1>>,
+{
+    /// Homogeneous matrix of `normalize_point`; singular when an axis is
+    /// collapsed.
+    pub fn normalizing_matrix(&self) -> Homogeneous<T, D> {
+        let half = T::one() / (T::one() + T::one());
+        let mut matrix = Homogeneous::<T, D>::identity();
+
+        for i in 0..D {
+            if self.is_collapsed(i) {
+                matrix[(i, i)] = T::zero();
+                matrix[(i, D)] = half.to_owned();
+            } else {
+                let inverse = T::one() / self.extents[i].to_owned();
+                matrix[(i, D)] = -self.origin[i].to_owned() * inverse.to_owned();
+                matrix[(i, i)] = inverse;
+            }
+        }
+
+        matrix
+    }
+
+    /// Homogeneous matrix of `denormalize_point`; singular when an axis is
+    /// collapsed.
+    pub fn denormalizing_matrix(&self) -> Homogeneous<T, D> {
+        let mut matrix = Homogeneous::<T, D>::identity();
+
+        for i in 0..D {
+            matrix[(i, i)] = self.extents[i].to_owned();
+            matrix[(i, D)] = self.origin[i].to_owned();
+        }
+
+        matrix
+    }
+
+    fn is_invertible(&self) -> bool {
+        (0..D).all(|i| !self.is_collapsed(i))
+    }
+
+    /// `normalizing_matrix` as an affine transform, `None` when an axis is
+    /// collapsed since affine transforms must be invertible.
+    pub fn to_normalizing_affine(&self) -> Option<Transform<T, TAffine, D>> {
+        self.is_invertible()
+            .then(|| Transform::from_matrix_unchecked(self.normalizing_matrix()))
+    }
+
+    /// `denormalizing_matrix` as an affine transform, `None` when an axis is
+    /// collapsed.
+    pub fn to_denormalizing_affine(&self) -> Option<Transform<T, TAffine, D>> {
+        self.is_invertible()
+            .then(|| Transform::from_matrix_unchecked(self.denormalizing_matrix()))
+    }
+}
+
+impl<T: RealField, const D: usize> BoundingSpaceN<T, D> {
+    pub fn unit_cube_map(&self) -> UnitCubeMap<T, D> {
+        UnitCubeMap::new(self)
+    }
+
+    /// Position of `point` relative to the space, with the space mapped to
+    /// `[0, 1]^D`. See [`UnitCubeMap`] for zero-width axes.
+    pub fn normalize_point(&self, point: &Point<T, D>) -> Point<T, D> {
+        self.unit_cube_map().normalize_point(point)
+    }
+
+    pub fn denormalize_point(&self, point: &Point<T, D>) -> Point<T, D> {
+        self.unit_cube_map().denormalize_point(point)
+    }
+
+    pub fn normalize_vector(&self, vector: &SVector<T, D>) -> SVector<T, D> {
+        self.unit_cube_map().normalize_vector(vector)
+    }
+
+    pub fn denormalize_vector(&self, vector: &SVector<T, D>) -> SVector<T, D> {
+        self.unit_cube_map().denormalize_vector(vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3, Vector2, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bound = BoundingSpaceN::new(Point3::new(-2.0, 0.0, 10.0), Point3::new(2.0, 5.0, 12.0));
+        let point = Point3::new(1.0, 4.0, 10.5);
+
+        let normalized = bound.normalize_point(&point);
+        assert_relative_eq!(normalized, Point3::new(0.75, 0.8, 0.25));
+        assert_relative_eq!(bound.denormalize_point(&normalized), point);
+        assert_relative_eq!(
+            bound.normalize_point(&bound.upper),
+            Point3::new(1.0, 1.0, 1.0)
+        );
+
+        let vector = Vector3::new(2.0, -1.0, 1.0);
+        assert_relative_eq!(
+            bound.normalize_vector(&vector),
+            Vector3::new(0.5, -0.2, 0.5)
+        );
+        assert_relative_eq!(
+            bound.denormalize_vector(&bound.normalize_vector(&vector)),
+            vector
+        );
+    }
+
+    #[test]
+    fn affine_matrices() {
+        let map =
+            BoundingSpaceN::new(Point2::new(1.0, -3.0), Point2::new(5.0, 1.0)).unit_cube_map();
+        let point = Point2::new(2.0, 0.0);
+
+        let normalize = map.to_normalizing_affine().unwrap();
+        let denormalize = map.to_denormalizing_affine().unwrap();
+        assert_relative_eq!(normalize * point, map.normalize_point(&point));
+        assert_relative_eq!(denormalize * (normalize * point), point);
+        assert_relative_eq!(
+            normalize.inverse().matrix(),
+            denormalize.matrix(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn collapsed_axes() {
+        let flat = BoundingSpaceN::new(Point2::new(0.0, 3.0), Point2::new(4.0, 3.0));
+        assert_relative_eq!(
+            flat.normalize_point(&Point2::new(1.0, 7.0)),
+            Point2::new(0.25, 0.5)
+        );
+        assert_relative_eq!(
+            flat.denormalize_point(&Point2::new(1.0, 0.9)),
+            Point2::new(4.0, 3.0)
+        );
+        assert_relative_eq!(
+            flat.normalize_vector(&Vector2::new(2.0, 2.0)),
+            Vector2::new(0.5, 0.0)
+        );
+
+        let map = flat.unit_cube_map();
+        assert_relative_eq!(
+            map.normalizing_matrix()
+                .transform_point(&Point2::new(1.0, 7.0)),
+            Point2::new(0.25, 0.5)
+        );
+        assert!(map.to_normalizing_affine().is_none());
+        assert!(map.to_denormalizing_affine().is_none());
+
+        let empty = BoundingSpaceN::<f64, 2>::empty();
+        assert_relative_eq!(
+            empty.normalize_point(&Point2::new(9.0, -9.0)),
+            Point2::new(0.5, 0.5)
+        );
+        assert_relative_eq!(
+            empty.denormalize_point(&Point2::new(0.3, 0.3)),
+            Point2::origin()
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use core::borrow::Borrow;
+
+use nalgebra::{Point, Point2, RealField, SMatrix, SVector};
+
+use crate::convex_hull::{hull_2d, min_area_direction};
+use crate::linalg::{covariance, symmetric_eigen};
+use crate::BoundingSpaceN;
+
+/// Box with arbitrary orientation: the columns of `rotation` are its local
+/// axes and `half_extents` its half widths along them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrientedBoundingSpaceN<T: RealField, const D: usize> {
+    pub center: Point<T, D>,
+    pub rotation: SMatrix<T, D, D>,
+    pub half_extents: SVector<T, D>,
+}
+
+pub type OrientedBoundingSpace2<T> = OrientedBoundingSpaceN<T, 2>;
+pub type OrientedBoundingSpace3<T> = OrientedBoundingSpaceN<T, 3>;
+
+impl<T: RealField, const D: usize> OrientedBoundingSpaceN<T, D> {
+    pub fn new(
+        center: Point<T, D>,
+        rotation: SMatrix<T, D, D>,
+        half_extents: SVector<T, D>,
+    ) -> Self {
+        Self {
+            center,
+            rotation,
+            half_extents,
+        }
+    }
+
+    /// Tightest box with the given orthonormal axes around the points.
+    pub fn fit_with_axes<I>(points: I, rotation: SMatrix<T, D, D>) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Point<T, D>>,
+    {
+        let inverse = rotation.transpose();
+        let local = BoundingSpaceN::from_points(
+            points
+                .into_iter()
+                .map(|p| Point::from(&inverse * &p.borrow().coords)),
+        )?;
+
+        Some(Self::new(
+            Point::from(&rotation * local.center().coords),
+            rotation,
+            local.half_extents(),
+        ))
+    }
+
+    /// Box aligned with the principal axes of the point covariance.
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Point<T, D>>,
+    {
+        let points: Vec<Point<T, D>> = points.into_iter().map(|p| p.borrow().to_owned()).collect();
+        if points.is_empty() {
+            return None;
+        }
+
+        let (_, covariance) = covariance(&points);
+        let (_, axes) = symmetric_eigen(&covariance);
+        Self::fit_with_axes(&points, axes)
+    }
+
+    /// Starts from the principal axes and repeatedly replaces each pair of
+    /// axes by the minimum-area rectangle of the points projected onto their
+    /// plane, until the volume stops shrinking. Exact in 2D, a close local
+    /// optimum in higher dimensions.
+    pub fn minimum_volume<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Point<T, D>>,
+    {
+        let points: Vec<Point<T, D>> = points.into_iter().map(|p| p.borrow().to_owned()).collect();
+        let mut best = Self::from_points(&points)?;
+        let shrink = T::one() - T::default_epsilon().sqrt();
+
+        for _ in 0..16 {
+            let mut improved = false;
+
+            for i in 0..D {
+                for j in i + 1..D {
+                    let (a, b) = (
+                        best.rotation.column(i).into_owned(),
+                        best.rotation.column(j).into_owned(),
+                    );
+                    let projected = points
+                        .iter()
+                        .map(|p| Point2::new(a.dot(&p.coords), b.dot(&p.coords)))
+                        .collect();
+                    let Some((u, _)) = min_area_direction(&hull_2d(projected)) else {
+                        continue;
+                    };
+
+                    let mut rotation = best.rotation.to_owned();
+                    rotation.set_column(i, &(&a * u.x.to_owned() + &b * u.y.to_owned()));
+                    rotation.set_column(j, &(&b * u.x.to_owned() - &a * u.y.to_owned()));
+                    let candidate = Self::fit_with_axes(&points, rotation)?;
+
+                    if candidate.volume() < best.volume() * shrink.to_owned() {
+                        best = candidate;
+                        improved = true;
+                    }
+                }
+            }
+
+            if !improved {
+                break;
+            }
+        }
+
+        Some(best)
+    }
+
+    pub fn volume(&self) -> T {
+        let two = T::one() + T::one();
+        self.half_extents
+            .iter()
+            .fold(T::one(), |acc, h| acc * h.to_owned() * two.to_owned())
+    }
+
+    pub fn axis(&self, index: usize) -> SVector<T, D> {
+        self.rotation.column(index).into_owned()
+    }
+
+    /// Coordinates of `point` along the box axes, relative to the center.
+    pub fn to_local(&self, point: &Point<T, D>) -> SVector<T, D> {
+        self.rotation.tr_mul(&(point - &self.center))
+    }
+
+    pub fn contains(&self, point: &Point<T, D>) -> bool {
+        self.to_local(point)
+            .iter()
+            .zip(&self.half_extents)
+            .all(|(l, h)| l.to_owned().abs() <= *h)
+    }
+
+    /// All `2^D` corner points of the box.
+    pub fn corners(&self) -> impl Iterator<Item = Point<T, D>> + '_ {
+        (0..1usize << D).map(move |mask| {
+            let local = SVector::<T, D>::from_fn(|i, _| {
+                if mask & (1 << i) == 0 {
+                    -self.half_extents[i].to_owned()
+                } else {
+                    self.half_extents[i].to_owned()
+                }
+            });
+            &self.center + &self.rotation * local
+        })
+    }
+
+    /// Axis-aligned space enclosing the box.
+    pub fn bounding_space(&self) -> BoundingSpaceN<T, D> {
+        let extents = self.rotation.abs() * &self.half_extents;
+        BoundingSpaceN::new(&self.center - &extents, &self.center + extents)
+    }
+
+    /// Half width of the box projected onto `axis`.
+    fn projected_radius(&self, axis: &SVector<T, D>) -> T {
+        (0..D).fold(T::zero(), |acc, i| {
+            acc + self.half_extents[i].to_owned() * axis.dot(&self.rotation.column(i)).abs()
+        })
+    }
+
+    /// Separating axis test. Candidate axes are the face normals of both
+    /// boxes plus, in 3D, the cross products of their edges, which makes the
+    /// test exact in 2D and 3D; in higher dimensions it may report overlap
+    /// for some separated boxes.
+    pub fn intersects(&self, other: &Self) -> bool {
+        let offset = &other.center - &self.center;
+        let parallel = T::default_epsilon().sqrt();
+        let separates = |axis: &SVector<T, D>| {
+            axis.norm_squared() > parallel
+                && axis.dot(&offset).abs()
+                    > self.projected_radius(axis) + other.projected_radius(axis)
+        };
+
+        if (0..D).any(|i| separates(&self.axis(i)) || separates(&other.axis(i))) {
+            return false;
+        }
+
+        if D == 3 {
+            for i in 0..3 {
+                for j in 0..3 {
+                    let (a, b) = (self.axis(i), other.axis(j));
+                    let cross = SVector::<T, D>::from_fn(|k, _| {
+                        let (k1, k2) = ((k + 1) % 3, (k + 2) % 3);
+                        a[k1].to_owned() * b[k2].to_owned() - a[k2].to_owned() * b[k1].to_owned()
+                    });
+                    if separates(&cross) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn intersects_space(&self, space: &BoundingSpaceN<T, D>) -> bool {
+        !space.is_empty() && self.intersects(&Self::from(space.to_owned()))
+    }
+}
+
+/// Box with the identity orientation. An empty space has no center and
+/// gives a box of zero size at the origin.
+impl<T: RealField, const D: usize> From<BoundingSpaceN<T, D>> for OrientedBoundingSpaceN<T, D> {
+    fn from(space: BoundingSpaceN<T, D>) -> Self {
+        Self::new(space.center(), SMatrix::identity(), space.half_extents())
+    }
+}
+
+impl<T: RealField, const D: usize> From<OrientedBoundingSpaceN<T, D>> for BoundingSpaceN<T, D> {
+    fn from(oriented: OrientedBoundingSpaceN<T, D>) -> Self {
+        oriented.bounding_space()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point3, Rotation2, Rotation3, Vector2, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn fitting_2d() {
+        let rotation = Rotation2::new(0.5).into_inner();
+        let points: Vec<_> = (0..40)
+            .map(|i| {
+                let t = i as f64;
+                let local = Vector2::new((t * 0.77).sin() * 4.0, (t * 1.31).cos());
+                Point2::from(rotation * local) + Vector2::new(3.0, -1.0)
+            })
+            .chain(
+                [
+                    Point2::from(rotation * Vector2::new(4.0, 1.0)),
+                    Point2::from(rotation * Vector2::new(-4.0, -1.0)),
+                ]
+                .map(|p| p + Vector2::new(3.0, -1.0)),
+            )
+            .collect();
+
+        let pca = OrientedBoundingSpace2::from_points(&points).unwrap();
+        let best = OrientedBoundingSpace2::minimum_volume(&points).unwrap();
+        let aabb = BoundingSpaceN::from_points(&points).unwrap();
+        for p in &points {
+            assert!((pca.to_local(p).abs() - pca.half_extents).max() < 1e-9);
+            assert!((best.to_local(p).abs() - best.half_extents).max() < 1e-9);
+        }
+        assert!(best.volume() <= pca.volume() + 1e-9);
+        assert!(best.volume() < aabb.volume());
+
+        let hull = hull_2d(points.clone());
+        let (_, area) = min_area_direction(&hull).unwrap();
+        assert_relative_eq!(best.volume(), area, epsilon = 1e-9);
+        assert!(
+            best.axis(0)
+                .dot(&Vector2::new(0.5f64.cos(), 0.5f64.sin()))
+                .abs()
+                > 0.999
+        );
+    }
+
+    #[test]
+    fn fitting_3d() {
+        let rotation = Rotation3::from_euler_angles(0.4, -0.2, 0.9).into_inner();
+        let half = Vector3::new(3.0, 1.5, 0.5);
+        let fitted = OrientedBoundingSpace3::new(Point3::new(1.0, 2.0, 3.0), rotation, half);
+        let points: Vec<_> = fitted
+            .corners()
+            .chain((0..20).map(|i| {
+                let t = i as f64;
+                let local = Vector3::new((t * 0.3).sin(), (t * 0.7).cos(), (t * 1.1).sin());
+                Point3::new(1.0, 2.0, 3.0) + rotation * local.component_mul(&half) * 0.9
+            }))
+            .collect();
+
+        let best = OrientedBoundingSpace3::minimum_volume(&points).unwrap();
+        assert_relative_eq!(best.volume(), fitted.volume(), epsilon = 1e-6);
+        assert_relative_eq!(best.center, fitted.center, epsilon = 1e-6);
+
+        let space = best.bounding_space();
+        assert!(points.iter().all(|p| space.contains(p)));
+        assert!(space.volume() > best.volume());
+    }
+
+    #[test]
+    fn separating_axes() {
+        let diamond = OrientedBoundingSpace2::new(
+            Point2::origin(),
+            Rotation2::new(core::f64::consts::FRAC_PI_4).into_inner(),
+            Vector2::new(1.0, 1.0),
+        );
+        let near = BoundingSpaceN::new(Point2::new(1.0, 1.0), Point2::new(3.0, 3.0));
+        assert!(diamond.bounding_space().intersects(&near));
+        assert!(!diamond.intersects_space(&near));
+        assert!(diamond.intersects_space(&BoundingSpaceN::new(
+            Point2::new(1.0, -0.5),
+            Point2::new(2.0, 0.5)
+        )));
+        assert!(diamond.contains(&Point2::new(1.4, 0.0)));
+        assert!(!diamond.contains(&Point2::new(1.0, 1.0)));
+
+        // Two cubes whose closest features are crossing edges, separated
+        // only along an edge-edge axis.
+        let a = OrientedBoundingSpace3::new(
+            Point3::origin(),
+            Rotation3::from_axis_angle(&Vector3::x_axis(), core::f64::consts::FRAC_PI_4)
+                .into_inner(),
+            Vector3::repeat(1.0),
+        );
+        let b_rotation =
+            Rotation3::from_axis_angle(&Vector3::z_axis(), core::f64::consts::FRAC_PI_4)
+                .into_inner();
+        let reach = 2f64.sqrt();
+        let b = OrientedBoundingSpace3::new(
+            Point3::new(0.0, reach * 2.0 + 0.05, 0.0),
+            b_rotation,
+            Vector3::repeat(1.0),
+        );
+        let mut c = b;
+        c.center.y -= 0.1;
+        assert!(!a.intersects(&b) && !b.intersects(&a));
+        assert!(a.intersects(&c) && c.intersects(&a));
+    }
+}
@@ -0,0 +1,436 @@
+//! Spatial subdivision into `2^D` orthants per node: a bintree in 1D, a
+//! quadtree in 2D and an octree in 3D.
+//!
+//! Items are stored in the deepest node whose cell contains them. In a loose
+//! tree every cell is enlarged about its center by the `looseness` factor so
+//! that small items straddling a split plane still sink down the tree.
+
+use nalgebra::{Point, RealField};
+
+use crate::BoundingSpaceN;
+
+impl<T: RealField, const D: usize> BoundingSpaceN<T, D> {
+    /// Child cell on the upper side of the center along every axis whose bit
+    /// is set in `index`, and on the lower side otherwise.
+    pub fn orthant(&self, index: usize) -> Self {
+        let center = self.center();
+        let mut child = self.to_owned();
+
+        for i in 0..D {
+            if index & (1 << i) == 0 {
+                child.upper[i] = center[i].to_owned();
+            } else {
+                child.lower[i] = center[i].to_owned();
+            }
+        }
+
+        child
+    }
+
+    /// Index of the orthant `point` falls in, ties going to the upper side.
+    pub fn orthant_index(&self, point: &Point<T, D>) -> usize {
+        let center = self.center();
+        (0..D).fold(0, |index, i| {
+            if point[i] >= center[i] {
+                index | (1 << i)
+            } else {
+                index
+            }
+        })
+    }
+
+    pub fn orthants(&self) -> impl Iterator<Item = Self> + '_ {
+        (0..1usize << D).map(move |index| self.orthant(index))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OrthtreeConfig<T> {
+    /// Items a node holds before it is subdivided.
+    pub capacity: usize,
+    pub max_depth: usize,
+    /// Factor of at least `1` by which cells are enlarged about their
+    /// centers, `1` for a regular tree.
+    pub looseness: T,
+}
+
+impl<T: RealField> Default for OrthtreeConfig<T> {
+    fn default() -> Self {
+        Self {
+            capacity: 8,
+            max_depth: 16,
+            looseness: T::one(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+#[derive(Debug, Clone)]
+struct OrthNode<T: RealField, const D: usize, V> {
+    cell: BoundingSpaceN<T, D>,
+    loose: BoundingSpaceN<T, D>,
+    depth: usize,
+    /// Index of the first of `2^D` consecutive children.
+    children: Option<usize>,
+    items: Vec<(BoundingSpaceN<T, D>, V)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Orthtree<T: RealField, const D: usize, V> {
+    nodes: Vec<OrthNode<T, D, V>>,
+    config: OrthtreeConfig<T>,
+    len: usize,
+}
+
+impl<T: RealField, const D: usize, V> Orthtree<T, D, V> {
+    pub fn new(domain: BoundingSpaceN<T, D>) -> Self {
+        Self::with_config(domain, OrthtreeConfig::default())
+    }
+
+    pub fn with_config(domain: BoundingSpaceN<T, D>, config: OrthtreeConfig<T>) -> Self {
+        assert!(config.looseness >= T::one(), "looseness must be at least 1");
+
+        let mut tree = Self {
+            nodes: Vec::new(),
+            config,
+            len: 0,
+        };
+        tree.push_node(domain, 0);
+        tree
+    }
+
+    fn push_node(&mut self, cell: BoundingSpaceN<T, D>, depth: usize) -> usize {
+        let mut loose = cell.to_owned();
+        loose.scale_about_center(self.config.looseness.to_owned());
+
+        self.nodes.push(OrthNode {
+            cell,
+            loose,
+            depth,
+            children: None,
+            items: Vec::new(),
+        });
+        self.nodes.len() - 1
+    }
+
+    pub fn domain(&self) -> &BoundingSpaceN<T, D> {
+        &self.nodes[0].cell
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Child of `node` that can hold `bound`, if any.
+    fn child_for(&self, node: usize, bound: &BoundingSpaceN<T, D>) -> Option<usize> {
+        let first = self.nodes[node].children?;
+        let child = first + self.nodes[node].cell.orthant_index(&bound.center());
+        self.nodes[child]
+            .loose
+            .contains_space(bound)
+            .then_some(child)
+    }
+
+    /// Inserts a value with the given bounds, handing it back when the
+    /// bounds do not fit in the domain.
+    pub fn insert(&mut self, bound: BoundingSpaceN<T, D>, value: V) -> Result<(), V> {
+        if bound.is_empty() || !self.nodes[0].loose.contains_space(&bound) {
+            return Err(value);
+        }
+
+        let mut node = 0;
+        while let Some(child) = self.child_for(node, &bound) {
+            node = child;
+        }
+
+        self.nodes[node].items.push((bound, value));
+        self.len += 1;
+        self.subdivide(node);
+        Ok(())
+    }
+
+    pub fn insert_point(&mut self, point: Point<T, D>, value: V) -> Result<(), V> {
+        self.insert(BoundingSpaceN::from_point(point), value)
+    }
+
+    fn subdivide(&mut self, node: usize) {
+        let OrthNode {
+            depth,
+            children,
+            ref items,
+            ..
+        } = self.nodes[node];
+        if children.is_some()
+            || items.len() <= self.config.capacity
+            || depth >= self.config.max_depth
+        {
+            return;
+        }
+
+        let first = self.nodes.len();
+        let cells: Vec<_> = self.nodes[node].cell.orthants().collect();
+        for cell in cells {
+            self.push_node(cell, depth + 1);
+        }
+        self.nodes[node].children = Some(first);
+
+        let items = core::mem::take(&mut self.nodes[node].items);
+        let mut touched = Vec::new();
+        for (bound, value) in items {
+            match self.child_for(node, &bound) {
+                Some(child) => {
+                    self.nodes[child].items.push((bound, value));
+                    touched.push(child);
+                }
+                None => self.nodes[node].items.push((bound, value)),
+            }
+        }
+
+        touched.sort_unstable();
+        touched.dedup();
+        for child in touched {
+            self.subdivide(child);
+        }
+    }
+
+    /// Removes the first value within `bound` accepted by `predicate`.
+    pub fn remove_with<F>(&mut self, bound: &BoundingSpaceN<T, D>, mut predicate: F) -> Option<V>
+    where
+        F: FnMut(&V) -> bool,
+    {
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            if !self.nodes[node].loose.intersects(bound) {
+                continue;
+            }
+
+            let position = self.nodes[node]
+                .items
+                .iter()
+                .position(|(b, v)| b.intersects(bound) && predicate(v));
+            if let Some(position) = position {
+                self.len -= 1;
+                return Some(self.nodes[node].items.swap_remove(position).1);
+            }
+
+            if let Some(first) = self.nodes[node].children {
+                stack.extend(first..first + (1 << D));
+            }
+        }
+
+        None
+    }
+
+    pub fn remove(&mut self, bound: &BoundingSpaceN<T, D>, value: &V) -> Option<V>
+    where
+        V: PartialEq,
+    {
+        self.remove_with(bound, |v| v == value)
+    }
+
+    fn search(
+        &self,
+        descend: impl Fn(&BoundingSpaceN<T, D>) -> bool,
+        test: impl Fn(&BoundingSpaceN<T, D>) -> bool,
+    ) -> Vec<&V> {
+        let mut found = Vec::new();
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if !descend(&node.loose) {
+                continue;
+            }
+
+            found.extend(
+                node.items
+                    .iter()
+                    .filter(|(bound, _)| test(bound))
+                    .map(|(_, value)| value),
+            );
+            if let Some(first) = node.children {
+                stack.extend(first..first + (1 << D));
+            }
+        }
+
+        found
+    }
+
+    pub fn query_overlap(&self, window: &BoundingSpaceN<T, D>) -> Vec<&V> {
+        self.search(|b| b.intersects(window), |b| b.intersects(window))
+    }
+
+    pub fn query_contained(&self, window: &BoundingSpaceN<T, D>) -> Vec<&V> {
+        self.search(|b| b.intersects(window), |b| window.contains_space(b))
+    }
+
+    pub fn query_point(&self, point: &Point<T, D>) -> Vec<&V> {
+        self.search(|b| b.contains(point), |b| b.contains(point))
+    }
+
+    /// Deepest node whose cell contains `point`.
+    pub fn leaf_at(&self, point: &Point<T, D>) -> Option<NodeId> {
+        if !self.nodes[0].cell.contains(point) {
+            return None;
+        }
+
+        let mut node = 0;
+        while let Some(first) = self.nodes[node].children {
+            node = first + self.nodes[node].cell.orthant_index(point);
+        }
+
+        Some(NodeId(node))
+    }
+
+    pub fn cell(&self, id: NodeId) -> &BoundingSpaceN<T, D> {
+        &self.nodes[id.0].cell
+    }
+
+    pub fn depth(&self, id: NodeId) -> usize {
+        self.nodes[id.0].depth
+    }
+
+    pub fn items(&self, id: NodeId) -> impl Iterator<Item = (&BoundingSpaceN<T, D>, &V)> {
+        self.nodes[id.0].items.iter().map(|(b, v)| (b, v))
+    }
+
+    /// Leaf cells sharing a `D - 1` dimensional face with the cell of `id`.
+    pub fn face_neighbors(&self, id: NodeId) -> Vec<NodeId> {
+        let cell = &self.nodes[id.0].cell;
+        let shares_face = |other: &BoundingSpaceN<T, D>| {
+            let mut touching = 0;
+            for i in 0..D {
+                if other.upper[i] == cell.lower[i] || other.lower[i] == cell.upper[i] {
+                    touching += 1;
+                } else if other.upper[i] < cell.lower[i] || other.lower[i] > cell.upper[i] {
+                    return false;
+                }
+            }
+            touching == 1
+        };
+
+        let mut found = Vec::new();
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let other = &self.nodes[node];
+            if node == id.0 || !other.cell.intersects(cell) {
+                continue;
+            }
+
+            match other.children {
+                Some(first) => stack.extend(first..first + (1 << D)),
+                None if shares_face(&other.cell) => found.push(NodeId(node)),
+                None => {}
+            }
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point1, Point2, Point3};
+
+    use super::*;
+
+    #[test]
+    fn orthants_partition_parent() {
+        let bound = BoundingSpaceN::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 4.0, 6.0));
+        let children: Vec<_> = bound.orthants().collect();
+
+        assert_eq!(children.len(), 8);
+        assert_relative_eq!(children[0].upper, Point3::new(1.0, 2.0, 3.0));
+        assert_relative_eq!(children[5].lower, Point3::new(1.0, 0.0, 3.0));
+        assert_relative_eq!(
+            children.iter().map(|c| c.volume()).sum::<f64>(),
+            bound.volume()
+        );
+        assert_eq!(bound.orthant_index(&Point3::new(1.5, 0.5, 5.0)), 5);
+    }
+
+    #[test]
+    fn quadtree_points() {
+        let domain = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(16.0, 16.0));
+        let mut tree = Orthtree::with_config(
+            domain,
+            OrthtreeConfig {
+                capacity: 2,
+                max_depth: 6,
+                looseness: 1.0,
+            },
+        );
+        for i in 0..16 {
+            for j in 0..16 {
+                tree.insert_point(Point2::new(i as f64 + 0.5, j as f64 + 0.5), (i, j))
+                    .unwrap();
+            }
+        }
+        assert!(tree.insert_point(Point2::new(17.0, 0.0), (99, 99)).is_err());
+        assert_eq!(tree.len(), 256);
+
+        let window = BoundingSpaceN::new(Point2::new(2.0, 3.0), Point2::new(4.0, 4.0));
+        let mut found: Vec<_> = tree.query_overlap(&window).into_iter().cloned().collect();
+        found.sort();
+        assert_eq!(found, vec![(2, 3), (3, 3)]);
+
+        let leaf = tree.leaf_at(&Point2::new(5.5, 5.5)).unwrap();
+        assert_relative_eq!(tree.cell(leaf).diagonal().x, 1.0);
+        assert_eq!(tree.face_neighbors(leaf).len(), 4);
+
+        let bound = BoundingSpaceN::from_point(Point2::new(5.5, 5.5));
+        assert_eq!(tree.remove(&bound, &(5, 5)), Some((5, 5)));
+        assert!(tree.query_point(&Point2::new(5.5, 5.5)).is_empty());
+    }
+
+    #[test]
+    fn loose_tree_sinks_straddling_boxes() {
+        let domain = BoundingSpaceN::new(Point1::new(0.0), Point1::new(8.0));
+        let config = |looseness| OrthtreeConfig {
+            capacity: 1,
+            max_depth: 3,
+            looseness,
+        };
+        let item = BoundingSpaceN::new(Point1::new(3.9), Point1::new(4.1));
+
+        for (looseness, depth) in [(1.0, 0), (2.0, 3)] {
+            let mut tree = Orthtree::with_config(domain, config(looseness));
+            for x in [1.0, 3.0, 5.0, 7.0] {
+                tree.insert_point(Point1::new(x), ()).unwrap();
+            }
+            tree.insert(item, ()).unwrap();
+
+            let mut stack = vec![NodeId(0)];
+            let mut item_depth = None;
+            while let Some(id) = stack.pop() {
+                if tree.items(id).any(|(b, _)| b.volume() > 0.0) {
+                    item_depth = Some(tree.depth(id));
+                }
+                if let Some(first) = tree.nodes[id.0].children {
+                    stack.extend((first..first + 2).map(NodeId));
+                }
+            }
+            assert_eq!(item_depth, Some(depth));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "looseness")]
+    fn looseness_is_validated() {
+        Orthtree::<f64, 2, ()>::with_config(
+            BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(1.0, 1.0)),
+            OrthtreeConfig {
+                looseness: 0.5,
+                ..OrthtreeConfig::default()
+            },
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use nalgebra::{Point, RealField, SVector};
+
+use crate::BoundingSpaceN;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ray<T: RealField, const D: usize> {
+    pub origin: Point<T, D>,
+    pub direction: SVector<T, D>,
+}
+
+impl<T: RealField, const D: usize> Ray<T, D> {
+    pub fn new(origin: Point<T, D>, direction: SVector<T, D>) -> Self {
+        Self { origin, direction }
+    }
+
+    pub fn from_segment(start: &Point<T, D>, end: &Point<T, D>) -> Self {
+        Self::new(start.to_owned(), end - start)
+    }
+
+    pub fn point_at(&self, t: T) -> Point<T, D> {
+        &self.origin + &self.direction * t
+    }
+
+    pub fn precompute(&self) -> PrecomputedRay<T, D> {
+        let mut parallel = [false; D];
+        let mut inv_direction = SVector::zeros();
+
+        for (i, d) in self.direction.iter().enumerate() {
+            if d.is_zero() {
+                parallel[i] = true;
+            } else {
+                inv_direction[i] = T::one() / d.to_owned();
+            }
+        }
+
+        PrecomputedRay {
+            origin: self.origin.to_owned(),
+            inv_direction,
+            parallel,
+        }
+    }
+}
+
+/// Ray with the reciprocal of its direction cached for repeated slab tests.
+/// Axes the ray is parallel to are flagged instead of holding an infinity.
+#[derive(Debug, Clone, Copy)]
+pub struct PrecomputedRay<T: RealField, const D: usize> {
+    pub origin: Point<T, D>,
+    pub inv_direction: SVector<T, D>,
+    pub parallel: [bool; D],
+}
+
+impl<T: RealField, const D: usize> From<&Ray<T, D>> for PrecomputedRay<T, D> {
+    fn from(ray: &Ray<T, D>) -> Self {
+        ray.precompute()
+    }
+}
+
+impl<T: RealField, const D: usize> BoundingSpaceN<T, D> {
+    /// Entry and exit parameters of the ray within `[t_min, t_max]`.
+    pub fn ray_intersection(&self, ray: &Ray<T, D>, t_min: T, t_max: T) -> Option<(T, T)> {
+        self.precomputed_ray_intersection(&ray.precompute(), t_min, t_max)
+    }
+
+    pub fn precomputed_ray_intersection(
+        &self,
+        ray: &PrecomputedRay<T, D>,
+        t_min: T,
+        t_max: T,
+    ) -> Option<(T, T)> {
+        let mut enter = t_min;
+        let mut exit = t_max;
+
+        for i in 0..D {
+            let (l, u, o) = (&self.lower[i], &self.upper[i], &ray.origin[i]);
+
+            if ray.parallel[i] {
+                if o < l || o > u {
+                    return None;
+                }
+                continue;
+            }
+
+            let inv = ray.inv_direction[i].to_owned();
+            let mut near = (l.to_owned() - o.to_owned()) * inv.to_owned();
+            let mut far = (u.to_owned() - o.to_owned()) * inv.to_owned();
+            if inv.is_negative() {
+                core::mem::swap(&mut near, &mut far);
+            }
+
+            enter = enter.max(near);
+            exit = exit.min(far);
+
+            if enter > exit {
+                return None;
+            }
+        }
+
+        Some((enter, exit))
+    }
+
+    /// Entry and exit parameters in `[0, 1]` along the segment from `start` to `end`.
+    pub fn segment_intersection(&self, start: &Point<T, D>, end: &Point<T, D>) -> Option<(T, T)> {
+        self.ray_intersection(&Ray::from_segment(start, end), T::zero(), T::one())
+    }
+
+    pub fn intersects_ray(&self, ray: &Ray<T, D>, t_min: T, t_max: T) -> bool {
+        self.ray_intersection(ray, t_min, t_max).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3, Vector2, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn ray_through_box() {
+        let bound = BoundingSpaceN::new(Point3::new(1.0, -1.0, -1.0), Point3::new(3.0, 1.0, 1.0));
+        let ray = Ray::new(Point3::origin(), Vector3::new(1.0, 0.0, 0.0));
+
+        let (enter, exit) = bound.ray_intersection(&ray, 0.0, f64::MAX).unwrap();
+        assert_relative_eq!(enter, 1.0);
+        assert_relative_eq!(exit, 3.0);
+
+        assert!(bound.ray_intersection(&ray, 0.0, 0.5).is_none());
+        assert!(bound.ray_intersection(&Ray::new(Point3::origin(), -ray.direction), 0.0, f64::MAX).is_none());
+
+        let offset = Ray::new(Point3::new(0.0, 2.0, 0.0), Vector3::new(1.0, 0.0, 0.0));
+        assert!(!bound.intersects_ray(&offset, 0.0, f64::MAX));
+    }
+
+    #[test]
+    fn ray_on_slab_boundary() {
+        let bound = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(1.0, 1.0));
+        let ray = Ray::new(Point2::new(-1.0, 1.0), Vector2::new(2.0, 0.0));
+
+        let (enter, exit) = bound.precomputed_ray_intersection(&ray.precompute(), 0.0, f64::MAX).unwrap();
+        assert_relative_eq!(enter, 0.5);
+        assert_relative_eq!(exit, 1.0);
+
+        let still = Ray::new(Point2::new(0.5, 0.5), Vector2::zeros());
+        assert!(bound.intersects_ray(&still, 0.0, 1.0));
+        assert!(!BoundingSpaceN::<f64, 2>::empty().intersects_ray(&ray, 0.0, f64::MAX));
+    }
+
+    #[test]
+    fn segment() {
+        let bound = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(1.0, 1.0));
+
+        let (enter, exit) = bound
+            .segment_intersection(&Point2::new(-1.0, 0.5), &Point2::new(3.0, 0.5))
+            .unwrap();
+        assert_relative_eq!(enter, 0.25);
+        assert_relative_eq!(exit, 0.5);
+
+        assert!(bound.segment_intersection(&Point2::new(-2.0, 0.5), &Point2::new(-1.0, 0.5)).is_none());
+    }
+}
@@ -0,0 +1,842 @@
+//! R-tree over values with `BoundingSpaceN` bounds.
+//!
+//! Bulk loading uses Sort-Tile-Recursive packing, single insertions follow
+//! the R*-tree: overlap-minimising subtree choice, forced reinsertion on the
+//! first overflow of a level and margin-driven splits.
+
+use core::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use nalgebra::{Point, RealField};
+
+use crate::{total_cmp, BoundingSpaceN};
+
+#[derive(Debug, Clone, Copy)]
+pub struct RTreeConfig {
+    pub max_entries: usize,
+    pub min_entries: usize,
+    /// Entries removed from an overflowing node for forced reinsertion.
+    pub reinsert_count: usize,
+}
+
+impl Default for RTreeConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 16,
+            min_entries: 6,
+            reinsert_count: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum RNode<T: RealField, const D: usize, V> {
+    Leaf(Vec<(BoundingSpaceN<T, D>, V)>),
+    Internal(Vec<(BoundingSpaceN<T, D>, RNode<T, D, V>)>),
+}
+
+impl<T: RealField, const D: usize, V> RNode<T, D, V> {
+    fn len(&self) -> usize {
+        match self {
+            RNode::Leaf(entries) => entries.len(),
+            RNode::Internal(children) => children.len(),
+        }
+    }
+
+    fn bound(&self) -> BoundingSpaceN<T, D> {
+        match self {
+            RNode::Leaf(entries) => bound_of(entries),
+            RNode::Internal(children) => bound_of(children),
+        }
+    }
+}
+
+/// Entry waiting to be placed in a node at `level`, leaves being level zero.
+enum Orphan<T: RealField, const D: usize, V> {
+    Value(BoundingSpaceN<T, D>, V),
+    Node(BoundingSpaceN<T, D>, RNode<T, D, V>),
+}
+
+type Entries<T, const D: usize, E> = Vec<(BoundingSpaceN<T, D>, E)>;
+
+fn bound_of<T: RealField, const D: usize, E>(
+    entries: &[(BoundingSpaceN<T, D>, E)],
+) -> BoundingSpaceN<T, D> {
+    entries
+        .iter()
+        .fold(BoundingSpaceN::empty(), |acc, (bound, _)| acc.union(bound))
+}
+
+fn margin<T: RealField, const D: usize>(bound: &BoundingSpaceN<T, D>) -> T {
+    bound.diagonal().sum()
+}
+
+/// Sort-Tile-Recursive packing of `entries` into groups of at most `max`.
+fn str_tiles<T: RealField, const D: usize, E>(
+    mut entries: Vec<(BoundingSpaceN<T, D>, E)>,
+    axis: usize,
+    max: usize,
+) -> Vec<Vec<(BoundingSpaceN<T, D>, E)>> {
+    entries.sort_by(|a, b| total_cmp(&a.0.center()[axis], &b.0.center()[axis]));
+
+    let groups = entries.len().div_ceil(max);
+    let slab_size = if axis + 1 >= D {
+        max
+    } else {
+        let slabs = (groups as f64).powf(1.0 / (D - axis) as f64).ceil() as usize;
+        entries.len().div_ceil(slabs.max(1))
+    };
+
+    let mut tiles = Vec::new();
+    let mut rest = entries;
+    while !rest.is_empty() {
+        let tail = rest.split_off(slab_size.min(rest.len()));
+        let slab = core::mem::replace(&mut rest, tail);
+        if axis + 1 >= D {
+            tiles.push(slab);
+        } else {
+            tiles.extend(str_tiles(slab, axis + 1, max));
+        }
+    }
+
+    tiles
+}
+
+/// R*-tree split: the axis minimising the summed margins of all candidate
+/// distributions, then the distribution with least overlap and volume.
+fn rstar_split<T: RealField, const D: usize, E>(
+    mut entries: Vec<(BoundingSpaceN<T, D>, E)>,
+    min: usize,
+) -> (Entries<T, D, E>, Entries<T, D, E>) {
+    let len = entries.len();
+    let min = min.clamp(1, len / 2);
+
+    let sort = |entries: &mut Vec<(BoundingSpaceN<T, D>, E)>, axis: usize, by_upper: bool| {
+        entries.sort_by(|a, b| {
+            if by_upper {
+                total_cmp(&a.0.upper[axis], &b.0.upper[axis])
+            } else {
+                total_cmp(&a.0.lower[axis], &b.0.lower[axis])
+            }
+        })
+    };
+
+    let distributions = |entries: &[(BoundingSpaceN<T, D>, E)]| {
+        let mut prefix = Vec::with_capacity(len);
+        let mut acc = BoundingSpaceN::empty();
+        for (bound, _) in entries {
+            acc.merge(bound);
+            prefix.push(acc.to_owned());
+        }
+
+        let mut suffix = vec![BoundingSpaceN::empty(); len + 1];
+        for i in (0..len).rev() {
+            suffix[i] = suffix[i + 1].union(&entries[i].0);
+        }
+
+        (min..=len - min)
+            .map(|k| (k, prefix[k - 1].to_owned(), suffix[k].to_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    let mut best_axis = (0, T::zero());
+    for axis in 0..D {
+        let mut total = T::zero();
+        for by_upper in [false, true] {
+            sort(&mut entries, axis, by_upper);
+            for (_, left, right) in distributions(&entries) {
+                total += margin(&left) + margin(&right);
+            }
+        }
+        if axis == 0 || total < best_axis.1 {
+            best_axis = (axis, total);
+        }
+    }
+
+    let mut best: Option<(T, T, bool, usize)> = None;
+    for by_upper in [false, true] {
+        sort(&mut entries, best_axis.0, by_upper);
+        for (k, left, right) in distributions(&entries) {
+            let overlap = left.intersection_volume(&right);
+            let volume = left.volume() + right.volume();
+            let better = best
+                .as_ref()
+                .is_none_or(|(o, v, _, _)| overlap < *o || (overlap == *o && volume < *v));
+            if better {
+                best = Some((overlap, volume, by_upper, k));
+            }
+        }
+    }
+
+    let (_, _, by_upper, k) = best.expect("split of an underfull node");
+    sort(&mut entries, best_axis.0, by_upper);
+    let right = entries.split_off(k);
+    (entries, right)
+}
+
+/// Removes the `count` entries whose centers lie farthest from the center of
+/// the node, farthest first, so popping them off the pending stack reinserts
+/// the closest first as in the R*-tree.
+fn take_farthest<T: RealField, const D: usize, E>(
+    entries: &mut Vec<(BoundingSpaceN<T, D>, E)>,
+    count: usize,
+) -> Vec<(BoundingSpaceN<T, D>, E)> {
+    let center = bound_of(entries).center();
+    entries.sort_by(|a, b| {
+        total_cmp(
+            &(a.0.center() - &center).norm_squared(),
+            &(b.0.center() - &center).norm_squared(),
+        )
+    });
+    let keep = entries.len() - count.min(entries.len());
+    let mut taken = entries.split_off(keep);
+    taken.reverse();
+    taken
+}
+
+fn choose_subtree<T: RealField, const D: usize, V>(
+    children: &[(BoundingSpaceN<T, D>, RNode<T, D, V>)],
+    bound: &BoundingSpaceN<T, D>,
+    leaves_below: bool,
+) -> usize {
+    let enlargement = |i: usize| children[i].0.union(bound).volume() - children[i].0.volume();
+    let overlap_enlargement = |i: usize| {
+        let enlarged = children[i].0.union(bound);
+        children.iter().enumerate().filter(|(j, _)| *j != i).fold(
+            T::zero(),
+            |acc, (_, (other, _))| {
+                acc + enlarged.intersection_volume(other) - children[i].0.intersection_volume(other)
+            },
+        )
+    };
+
+    let key = |i: usize| {
+        let overlap = if leaves_below {
+            overlap_enlargement(i)
+        } else {
+            T::zero()
+        };
+        (overlap, enlargement(i), children[i].0.volume())
+    };
+
+    (0..children.len())
+        .map(|i| (i, key(i)))
+        .min_by(|(_, a), (_, b)| {
+            total_cmp(&a.0, &b.0)
+                .then_with(|| total_cmp(&a.1, &b.1))
+                .then_with(|| total_cmp(&a.2, &b.2))
+        })
+        .map(|(i, _)| i)
+        .expect("internal node without children")
+}
+
+#[derive(Debug, Clone)]
+pub struct RTree<T: RealField, const D: usize, V> {
+    root: RNode<T, D, V>,
+    height: usize,
+    len: usize,
+    config: RTreeConfig,
+}
+
+impl<T: RealField, const D: usize, V> Default for RTree<T, D, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: RealField, const D: usize, V> RTree<T, D, V> {
+    pub fn new() -> Self {
+        Self::with_config(RTreeConfig::default())
+    }
+
+    pub fn with_config(config: RTreeConfig) -> Self {
+        assert!(
+            config.max_entries >= 2,
+            "nodes must hold at least two entries"
+        );
+        assert!(
+            config.min_entries >= 1 && config.min_entries <= config.max_entries / 2,
+            "minimum fill must lie in 1..=max_entries / 2"
+        );
+        assert!(
+            config.reinsert_count >= 1
+                && config.reinsert_count <= config.max_entries - config.min_entries,
+            "reinsert count must lie in 1..=max_entries - min_entries"
+        );
+
+        Self {
+            root: RNode::Leaf(Vec::new()),
+            height: 0,
+            len: 0,
+            config,
+        }
+    }
+
+    pub fn bulk_load(entries: Vec<(BoundingSpaceN<T, D>, V)>) -> Self {
+        Self::bulk_load_with_config(entries, RTreeConfig::default())
+    }
+
+    pub fn bulk_load_with_config(
+        entries: Vec<(BoundingSpaceN<T, D>, V)>,
+        config: RTreeConfig,
+    ) -> Self {
+        let mut tree = Self::with_config(config);
+        tree.len = entries.len();
+        if entries.is_empty() {
+            return tree;
+        }
+
+        let max = config.max_entries;
+        let mut level: Vec<(BoundingSpaceN<T, D>, RNode<T, D, V>)> = str_tiles(entries, 0, max)
+            .into_iter()
+            .map(|tile| (bound_of(&tile), RNode::Leaf(tile)))
+            .collect();
+
+        while level.len() > 1 {
+            level = str_tiles(level, 0, max)
+                .into_iter()
+                .map(|tile| (bound_of(&tile), RNode::Internal(tile)))
+                .collect();
+            tree.height += 1;
+        }
+
+        tree.root = level.pop().expect("at least one node").1;
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bound(&self) -> BoundingSpaceN<T, D> {
+        self.root.bound()
+    }
+
+    pub fn insert(&mut self, bound: BoundingSpaceN<T, D>, value: V) {
+        self.len += 1;
+        self.place(vec![(Orphan::Value(bound, value), 0)]);
+    }
+
+    fn place(&mut self, mut pending: Vec<(Orphan<T, D, V>, usize)>) {
+        let mut reinserted = vec![false; self.height + 1];
+
+        while let Some((orphan, level)) = pending.pop() {
+            if level > self.height {
+                // The tree shrank below this subtree: scatter its children.
+                if let Orphan::Node(_, node) = orphan {
+                    match node {
+                        RNode::Leaf(entries) => pending
+                            .extend(entries.into_iter().map(|(b, v)| (Orphan::Value(b, v), 0))),
+                        RNode::Internal(children) => pending.extend(
+                            children
+                                .into_iter()
+                                .map(|(b, n)| (Orphan::Node(b, n), level - 1)),
+                        ),
+                    }
+                }
+                continue;
+            }
+
+            let height = self.height;
+            let config = self.config;
+            let split = Self::insert_into(
+                &mut self.root,
+                height,
+                true,
+                orphan,
+                level,
+                &config,
+                &mut reinserted,
+                &mut pending,
+            );
+
+            if let Some(sibling) = split {
+                let old = core::mem::replace(&mut self.root, RNode::Leaf(Vec::new()));
+                self.root = RNode::Internal(vec![(old.bound(), old), sibling]);
+                self.height += 1;
+                reinserted.push(false);
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn insert_into(
+        node: &mut RNode<T, D, V>,
+        node_level: usize,
+        is_root: bool,
+        orphan: Orphan<T, D, V>,
+        level: usize,
+        config: &RTreeConfig,
+        reinserted: &mut [bool],
+        pending: &mut Vec<(Orphan<T, D, V>, usize)>,
+    ) -> Option<(BoundingSpaceN<T, D>, RNode<T, D, V>)> {
+        if node_level == level {
+            match (&mut *node, orphan) {
+                (RNode::Leaf(entries), Orphan::Value(bound, value)) => entries.push((bound, value)),
+                (RNode::Internal(children), Orphan::Node(bound, child)) => {
+                    children.push((bound, child))
+                }
+                _ => unreachable!("entry placed at the wrong level"),
+            }
+        } else {
+            let RNode::Internal(children) = &mut *node else {
+                unreachable!("leaf above the target level");
+            };
+
+            let bound = match &orphan {
+                Orphan::Value(bound, _) | Orphan::Node(bound, _) => bound.to_owned(),
+            };
+            let index = choose_subtree(children, &bound, node_level == 1);
+            let split = Self::insert_into(
+                &mut children[index].1,
+                node_level - 1,
+                false,
+                orphan,
+                level,
+                config,
+                reinserted,
+                pending,
+            );
+            children[index].0 = children[index].1.bound();
+            children.extend(split);
+        }
+
+        if node.len() <= config.max_entries {
+            return None;
+        }
+
+        if !is_root && !reinserted[node_level] {
+            reinserted[node_level] = true;
+            match node {
+                RNode::Leaf(entries) => pending.extend(
+                    take_farthest(entries, config.reinsert_count)
+                        .into_iter()
+                        .map(|(b, v)| (Orphan::Value(b, v), node_level)),
+                ),
+                RNode::Internal(children) => pending.extend(
+                    take_farthest(children, config.reinsert_count)
+                        .into_iter()
+                        .map(|(b, n)| (Orphan::Node(b, n), node_level)),
+                ),
+            }
+            return None;
+        }
+
+        let sibling = match node {
+            RNode::Leaf(entries) => {
+                let (left, right) = rstar_split(core::mem::take(entries), config.min_entries);
+                *entries = left;
+                RNode::Leaf(right)
+            }
+            RNode::Internal(children) => {
+                let (left, right) = rstar_split(core::mem::take(children), config.min_entries);
+                *children = left;
+                RNode::Internal(right)
+            }
+        };
+
+        Some((sibling.bound(), sibling))
+    }
+
+    /// Removes the first value whose bounds overlap `bound` and that is
+    /// accepted by `predicate`, reinserting the entries of nodes left
+    /// underfull.
+    pub fn remove_with<F>(&mut self, bound: &BoundingSpaceN<T, D>, mut predicate: F) -> Option<V>
+    where
+        F: FnMut(&V) -> bool,
+    {
+        let mut orphans = Vec::new();
+        let removed = Self::remove_from(
+            &mut self.root,
+            self.height,
+            bound,
+            &mut predicate,
+            self.config.min_entries,
+            &mut orphans,
+        )?;
+        self.len -= 1;
+
+        if self.root.len() == 0 {
+            self.root = RNode::Leaf(Vec::new());
+            self.height = 0;
+        }
+
+        self.place(orphans);
+
+        while self.height > 0 && self.root.len() == 1 {
+            if let RNode::Internal(children) = &mut self.root {
+                self.root = children.pop().expect("single child").1;
+                self.height -= 1;
+            }
+        }
+
+        Some(removed)
+    }
+
+    pub fn remove(&mut self, bound: &BoundingSpaceN<T, D>, value: &V) -> Option<V>
+    where
+        V: PartialEq,
+    {
+        self.remove_with(bound, |v| v == value)
+    }
+
+    fn remove_from<F>(
+        node: &mut RNode<T, D, V>,
+        node_level: usize,
+        bound: &BoundingSpaceN<T, D>,
+        predicate: &mut F,
+        min: usize,
+        orphans: &mut Vec<(Orphan<T, D, V>, usize)>,
+    ) -> Option<V>
+    where
+        F: FnMut(&V) -> bool,
+    {
+        match node {
+            RNode::Leaf(entries) => {
+                let index = entries
+                    .iter()
+                    .position(|(b, v)| b.intersects(bound) && predicate(v))?;
+                Some(entries.swap_remove(index).1)
+            }
+            RNode::Internal(children) => {
+                for index in 0..children.len() {
+                    if !children[index].0.intersects(bound) {
+                        continue;
+                    }
+
+                    let Some(removed) = Self::remove_from(
+                        &mut children[index].1,
+                        node_level - 1,
+                        bound,
+                        predicate,
+                        min,
+                        orphans,
+                    ) else {
+                        continue;
+                    };
+
+                    if children[index].1.len() < min {
+                        match children.swap_remove(index).1 {
+                            RNode::Leaf(entries) => orphans
+                                .extend(entries.into_iter().map(|(b, v)| (Orphan::Value(b, v), 0))),
+                            RNode::Internal(grand_children) => orphans.extend(
+                                grand_children
+                                    .into_iter()
+                                    .map(|(b, n)| (Orphan::Node(b, n), node_level - 1)),
+                            ),
+                        }
+                    } else {
+                        children[index].0 = children[index].1.bound();
+                    }
+
+                    return Some(removed);
+                }
+
+                None
+            }
+        }
+    }
+
+    /// Visits values whose bounds pass `test`, descending only into nodes
+    /// whose bounds pass `descend`.
+    fn search(
+        &self,
+        descend: impl Fn(&BoundingSpaceN<T, D>) -> bool,
+        test: impl Fn(&BoundingSpaceN<T, D>) -> bool,
+    ) -> Vec<&V> {
+        let mut found = Vec::new();
+        let mut stack = vec![&self.root];
+
+        while let Some(node) = stack.pop() {
+            match node {
+                RNode::Leaf(entries) => found.extend(
+                    entries
+                        .iter()
+                        .filter(|(bound, _)| test(bound))
+                        .map(|(_, value)| value),
+                ),
+                RNode::Internal(children) => stack.extend(
+                    children
+                        .iter()
+                        .filter(|(bound, _)| descend(bound))
+                        .map(|(_, child)| child),
+                ),
+            }
+        }
+
+        found
+    }
+
+    /// Values whose bounds overlap the window.
+    pub fn query_overlap(&self, window: &BoundingSpaceN<T, D>) -> Vec<&V> {
+        self.search(|b| b.intersects(window), |b| b.intersects(window))
+    }
+
+    /// Values whose bounds lie entirely within the window.
+    pub fn query_contained(&self, window: &BoundingSpaceN<T, D>) -> Vec<&V> {
+        self.search(|b| b.intersects(window), |b| window.contains_space(b))
+    }
+
+    /// Values whose bounds contain the whole of `bound`.
+    pub fn query_containing(&self, bound: &BoundingSpaceN<T, D>) -> Vec<&V> {
+        self.search(|b| b.contains_space(bound), |b| b.contains_space(bound))
+    }
+
+    pub fn query_point(&self, point: &Point<T, D>) -> Vec<&V> {
+        self.search(|b| b.contains(point), |b| b.contains(point))
+    }
+
+    /// Values in ascending order of the squared distance from `point` to
+    /// their bounds, produced lazily by best-first search.
+    pub fn nearest_neighbors<'s>(&'s self, point: &Point<T, D>) -> NearestNeighbors<'s, T, D, V> {
+        let mut heap = BinaryHeap::new();
+        heap.push(Candidate {
+            distance: T::zero(),
+            kind: CandidateKind::Node(&self.root),
+        });
+
+        NearestNeighbors {
+            point: point.to_owned(),
+            heap,
+        }
+    }
+
+    pub fn k_nearest(&self, point: &Point<T, D>, k: usize) -> Vec<(&V, T)> {
+        self.nearest_neighbors(point).take(k).collect()
+    }
+}
+
+enum CandidateKind<'a, T: RealField, const D: usize, V> {
+    Node(&'a RNode<T, D, V>),
+    Value(&'a V),
+}
+
+struct Candidate<'a, T: RealField, const D: usize, V> {
+    distance: T,
+    kind: CandidateKind<'a, T, D, V>,
+}
+
+impl<T: RealField, const D: usize, V> PartialEq for Candidate<'_, T, D, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: RealField, const D: usize, V> Eq for Candidate<'_, T, D, V> {}
+
+impl<T: RealField, const D: usize, V> PartialOrd for Candidate<'_, T, D, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: RealField, const D: usize, V> Ord for Candidate<'_, T, D, V> {
+    /// Reversed so the max-heap pops the closest candidate, values before
+    /// nodes at equal distance.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let rank = |kind: &CandidateKind<'_, T, D, V>| match kind {
+            CandidateKind::Value(_) => 0,
+            CandidateKind::Node(_) => 1,
+        };
+
+        total_cmp(&other.distance, &self.distance).then(rank(&other.kind).cmp(&rank(&self.kind)))
+    }
+}
+
+pub struct NearestNeighbors<'a, T: RealField, const D: usize, V> {
+    point: Point<T, D>,
+    heap: BinaryHeap<Candidate<'a, T, D, V>>,
+}
+
+impl<'a, T: RealField, const D: usize, V> Iterator for NearestNeighbors<'a, T, D, V> {
+    /// Value and squared distance from the query point to its bounds.
+    type Item = (&'a V, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Candidate { distance, kind }) = self.heap.pop() {
+            match kind {
+                CandidateKind::Value(value) => return Some((value, distance)),
+                CandidateKind::Node(RNode::Leaf(entries)) => {
+                    for (bound, value) in entries {
+                        self.heap.push(Candidate {
+                            distance: bound.distance_squared_to_point(&self.point),
+                            kind: CandidateKind::Value(value),
+                        });
+                    }
+                }
+                CandidateKind::Node(RNode::Internal(children)) => {
+                    for (bound, child) in children {
+                        self.heap.push(Candidate {
+                            distance: bound.distance_squared_to_point(&self.point),
+                            kind: CandidateKind::Node(child),
+                        });
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Vector2};
+
+    use super::*;
+    use crate::BoundingSquare;
+
+    fn footprints(n: usize) -> Vec<(BoundingSquare<f64>, usize)> {
+        (0..n * n)
+            .map(|i| {
+                let lower = Point2::new((i % n) as f64 * 2.0, (i / n) as f64 * 2.0);
+                (BoundingSpaceN::new(lower, lower + Vector2::repeat(1.0)), i)
+            })
+            .collect()
+    }
+
+    fn sorted(values: Vec<&usize>) -> Vec<usize> {
+        let mut values: Vec<usize> = values.into_iter().cloned().collect();
+        values.sort();
+        values
+    }
+
+    fn check_queries(tree: &RTree<f64, 2, usize>, entries: &[(BoundingSquare<f64>, usize)]) {
+        let window = BoundingSpaceN::new(Point2::new(3.5, 3.5), Point2::new(9.0, 7.5));
+        let expected = |f: &dyn Fn(&BoundingSquare<f64>) -> bool| {
+            let mut values: Vec<usize> = entries
+                .iter()
+                .filter(|(b, _)| f(b))
+                .map(|(_, v)| *v)
+                .collect();
+            values.sort();
+            values
+        };
+
+        assert_eq!(
+            sorted(tree.query_overlap(&window)),
+            expected(&|b| b.intersects(&window))
+        );
+        assert_eq!(
+            sorted(tree.query_contained(&window)),
+            expected(&|b| window.contains_space(b))
+        );
+    }
+
+    #[test]
+    fn bulk_load_queries() {
+        let entries = footprints(20);
+        let tree = RTree::bulk_load(entries.clone());
+
+        assert_eq!(tree.len(), 400);
+        assert_eq!(tree.height(), 2);
+        check_queries(&tree, &entries);
+        assert_eq!(tree.query_point(&Point2::new(4.5, 2.5)), vec![&22]);
+        assert_eq!(
+            tree.query_containing(&BoundingSpaceN::from_point(Point2::new(4.5, 2.5))),
+            vec![&22]
+        );
+    }
+
+    #[test]
+    fn rstar_insert_and_remove() {
+        let entries = footprints(15);
+        let mut tree = RTree::with_config(RTreeConfig {
+            max_entries: 6,
+            min_entries: 2,
+            reinsert_count: 2,
+        });
+        for (bound, value) in entries.iter().cloned() {
+            tree.insert(bound, value);
+        }
+
+        assert_eq!(tree.len(), entries.len());
+        check_queries(&tree, &entries);
+
+        let (kept, removed): (Vec<_>, Vec<_>) = entries.into_iter().partition(|(_, v)| v % 3 == 0);
+        for (bound, value) in &removed {
+            assert_eq!(tree.remove(bound, value), Some(*value));
+        }
+        assert!(tree.remove(&removed[0].0, &removed[0].1).is_none());
+
+        assert_eq!(tree.len(), kept.len());
+        check_queries(&tree, &kept);
+
+        for (bound, value) in &kept {
+            assert_eq!(tree.remove(bound, value), Some(*value));
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.height(), 0);
+        tree.insert(kept[0].0, kept[0].1);
+        assert_eq!(tree.query_point(&kept[0].0.center()), vec![&kept[0].1]);
+    }
+
+    #[test]
+    fn remove_within_window() {
+        let entries = footprints(15);
+        let mut tree = RTree::with_config(RTreeConfig {
+            max_entries: 4,
+            min_entries: 2,
+            reinsert_count: 1,
+        });
+        for (bound, value) in entries.iter().cloned() {
+            tree.insert(bound, value);
+        }
+        assert!(tree.height() > 1);
+
+        let everything =
+            BoundingSpaceN::new(Point2::new(-1000.0, -1000.0), Point2::new(1000.0, 1000.0));
+        assert_eq!(tree.remove_with(&everything, |v| *v == 57), Some(57));
+        assert_eq!(tree.remove_with(&everything, |v| *v == 57), None);
+
+        let window = BoundingSpaceN::new(Point2::new(3.5, 3.5), Point2::new(9.0, 7.5));
+        let inside = sorted(tree.query_overlap(&window));
+        let mut removed = Vec::new();
+        while let Some(value) = tree.remove_with(&window, |_| true) {
+            removed.push(value);
+        }
+        removed.sort();
+        assert_eq!(removed, inside);
+        assert_eq!(tree.len(), entries.len() - 1 - inside.len());
+        assert!(tree.query_overlap(&window).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "reinsert count")]
+    fn reinsert_count_is_validated() {
+        RTree::<f64, 2, usize>::with_config(RTreeConfig {
+            max_entries: 4,
+            min_entries: 2,
+            reinsert_count: 0,
+        });
+    }
+
+    #[test]
+    fn nearest_neighbors() {
+        let entries = footprints(10);
+        let tree = RTree::bulk_load(entries.clone());
+        let point = Point2::new(7.25, 3.0);
+
+        let nearest = tree.k_nearest(&point, 5);
+        let mut expected: Vec<f64> = entries
+            .iter()
+            .map(|(b, _)| b.distance_squared_to_point(&point))
+            .collect();
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert_eq!(*nearest[0].0, 13);
+        for ((_, distance), expected) in nearest.iter().zip(&expected) {
+            assert_relative_eq!(*distance, *expected);
+        }
+        assert_eq!(tree.nearest_neighbors(&point).count(), 100);
+    }
+}
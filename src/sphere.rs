@@ -0,0 +1,368 @@
+use core::borrow::Borrow;
+
+use nalgebra::{convert, Point, RealField};
+
+use crate::linalg::solve;
+use crate::BoundingSpaceN;
+
+fn farthest_from<'a, T: RealField, const D: usize>(
+    points: &'a [Point<T, D>],
+    from: &'a Point<T, D>,
+) -> &'a Point<T, D> {
+    points
+        .iter()
+        .fold((from, T::zero()), |(best, distance), p| {
+            let d = (p - from).norm_squared();
+            if d > distance {
+                (p, d)
+            } else {
+                (best, distance)
+            }
+        })
+        .0
+}
+
+/// Ball given by its center and radius.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphereN<T: RealField, const D: usize> {
+    pub center: Point<T, D>,
+    pub radius: T,
+}
+
+pub type BoundingCircle<T> = BoundingSphereN<T, 2>;
+pub type BoundingSphere<T> = BoundingSphereN<T, 3>;
+
+impl<T: RealField, const D: usize> BoundingSphereN<T, D> {
+    pub fn new(center: Point<T, D>, radius: T) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn from_point(point: Point<T, D>) -> Self {
+        Self::new(point, T::zero())
+    }
+
+    /// Ritter's approximation: a sphere on the diameter between two far apart
+    /// points, grown to take in the remaining ones. Usually within a few
+    /// percent of the minimum radius.
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Point<T, D>>,
+    {
+        let points: Vec<Point<T, D>> = points.into_iter().map(|p| p.borrow().to_owned()).collect();
+        let first = points.first()?;
+        let a = farthest_from(&points, first);
+        let b = farthest_from(&points, a);
+
+        let two = T::one() + T::one();
+        let mut sphere = Self::new(nalgebra::center(a, b), (b - a).norm() / two);
+        for point in &points {
+            sphere.expand(point);
+        }
+
+        Some(sphere)
+    }
+
+    /// Exact minimum enclosing ball by Welzl's algorithm with the
+    /// move-to-front heuristic. The radius is then reset to the largest
+    /// distance to any point, with a few ulps of slack, so `contains` accepts
+    /// every input point despite the tolerance Welzl's tests need.
+    pub fn minimum_enclosing<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Point<T, D>>,
+    {
+        let mut points: Vec<Point<T, D>> =
+            points.into_iter().map(|p| p.borrow().to_owned()).collect();
+        let end = points.len();
+        let mut ball = Self::welzl(&mut points, end, &mut Vec::with_capacity(D + 1))?;
+
+        let reach = points
+            .iter()
+            .fold(T::zero(), |acc, p| acc.max((p - &ball.center).norm()));
+        ball.radius = reach * (T::one() + T::default_epsilon() * nalgebra::convert(4.0));
+        Some(ball)
+    }
+
+    fn welzl(
+        points: &mut [Point<T, D>],
+        end: usize,
+        boundary: &mut Vec<Point<T, D>>,
+    ) -> Option<Self> {
+        let mut ball = Self::circumsphere(boundary);
+        if boundary.len() == D + 1 {
+            return ball;
+        }
+
+        for i in 0..end {
+            if ball.as_ref().is_some_and(|b| b.encloses(&points[i])) {
+                continue;
+            }
+
+            boundary.push(points[i].to_owned());
+            ball = Self::welzl(points, i, boundary);
+            boundary.pop();
+            points[..=i].rotate_right(1);
+        }
+
+        ball
+    }
+
+    /// Containment with a relative slack so points of the boundary set are
+    /// not rejected by rounding.
+    fn encloses(&self, point: &Point<T, D>) -> bool {
+        let slack = T::one() + T::default_epsilon().sqrt();
+        (point - &self.center).norm() <= self.radius.to_owned() * slack
+    }
+
+    /// Smallest sphere with all of `boundary` on its surface.
+    fn circumsphere(boundary: &[Point<T, D>]) -> Option<Self> {
+        let (origin, rest) = boundary.split_first()?;
+        let offsets: Vec<_> = rest.iter().map(|p| p - origin).collect();
+
+        let gram = offsets
+            .iter()
+            .map(|u| offsets.iter().map(|v| u.dot(v)).collect())
+            .collect();
+        let two = T::one() + T::one();
+        let rhs = offsets
+            .iter()
+            .map(|v| v.norm_squared() / two.to_owned())
+            .collect();
+
+        match solve(gram, rhs) {
+            Some(weights) => {
+                let center = offsets
+                    .iter()
+                    .zip(weights)
+                    .fold(origin.to_owned(), |acc, (v, w)| acc + v * w);
+                let radius = boundary
+                    .iter()
+                    .fold(T::zero(), |acc, p| acc.max((p - &center).norm()));
+                Some(Self::new(center, radius))
+            }
+            None => {
+                // Affinely dependent points: the last one cannot be needed
+                // on the surface, so just take it in.
+                let (last, others) = boundary.split_last()?;
+                let mut sphere = Self::circumsphere(others)?;
+                sphere.expand(last);
+                Some(sphere)
+            }
+        }
+    }
+
+    pub fn contains(&self, point: &Point<T, D>) -> bool {
+        (point - &self.center).norm() <= self.radius
+    }
+
+    pub fn contains_sphere(&self, other: &Self) -> bool {
+        (&other.center - &self.center).norm() + other.radius.to_owned() <= self.radius
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        let reach = self.radius.to_owned() + other.radius.to_owned();
+        (&other.center - &self.center).norm_squared() <= reach.to_owned() * reach
+    }
+
+    pub fn intersects_space(&self, space: &BoundingSpaceN<T, D>) -> bool {
+        !space.is_empty()
+            && space.distance_squared_to_point(&self.center)
+                <= self.radius.to_owned() * self.radius.to_owned()
+    }
+
+    /// Grows the sphere just enough to contain `point`, keeping the side
+    /// opposite the point fixed.
+    pub fn expand(&mut self, point: &Point<T, D>) {
+        let offset = point - &self.center;
+        let distance = offset.norm();
+        if distance <= self.radius {
+            return;
+        }
+
+        let two = T::one() + T::one();
+        let radius = (self.radius.to_owned() + distance.to_owned()) / two;
+        self.center += offset * ((radius.to_owned() - self.radius.to_owned()) / distance);
+        // Guard against rounding leaving the point just outside.
+        self.radius = radius.max((point - &self.center).norm());
+    }
+
+    /// Smallest sphere containing both spheres.
+    pub fn merge(&mut self, other: &Self) {
+        let offset = &other.center - &self.center;
+        let distance = offset.norm();
+
+        if distance.to_owned() + other.radius.to_owned() <= self.radius {
+            return;
+        }
+        if distance.to_owned() + self.radius.to_owned() <= other.radius {
+            *self = other.to_owned();
+            return;
+        }
+
+        let two = T::one() + T::one();
+        let radius = (distance.to_owned() + self.radius.to_owned() + other.radius.to_owned()) / two;
+        self.center += offset * ((radius.to_owned() - self.radius.to_owned()) / distance);
+        self.radius = radius;
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut sphere = self.to_owned();
+        sphere.merge(other);
+        sphere
+    }
+
+    pub fn volume(&self) -> T {
+        let two_pi = T::two_pi();
+        let mut unit: T = if D % 2 == 0 { T::one() } else { convert(2.0) };
+        for n in (2 + D % 2..=D).step_by(2) {
+            unit *= two_pi.to_owned() / convert(n as f64);
+        }
+
+        unit * self.radius.to_owned().powi(D as i32)
+    }
+
+    /// Axis-aligned space enclosing the sphere.
+    pub fn bounding_space(&self) -> BoundingSpaceN<T, D> {
+        let offset = nalgebra::SVector::<T, D>::repeat(self.radius.to_owned());
+        BoundingSpaceN::new(&self.center - &offset, &self.center + offset)
+    }
+}
+
+impl<T: RealField, const D: usize> BoundingSpaceN<T, D> {
+    /// Circumscribed sphere, centered with the corners on its surface. An
+    /// empty space gives a zero radius sphere at the origin.
+    pub fn bounding_sphere(&self) -> BoundingSphereN<T, D> {
+        BoundingSphereN::new(self.center(), self.half_extents().norm())
+    }
+}
+
+impl<T: RealField, const D: usize> From<BoundingSpaceN<T, D>> for BoundingSphereN<T, D> {
+    fn from(space: BoundingSpaceN<T, D>) -> Self {
+        space.bounding_sphere()
+    }
+}
+
+impl<T: RealField, const D: usize> From<BoundingSphereN<T, D>> for BoundingSpaceN<T, D> {
+    fn from(sphere: BoundingSphereN<T, D>) -> Self {
+        sphere.bounding_space()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Point2, Point3};
+
+    use super::*;
+
+    fn cloud() -> Vec<Point3<f64>> {
+        (0..300)
+            .map(|i| {
+                let t = i as f64;
+                Point3::new(
+                    (t * 0.37).sin() * 4.0,
+                    (t * 0.91).cos() * 2.5,
+                    (t * 0.23).sin() * (t * 0.13).cos() * 3.0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn minimum_enclosing_ball() {
+        let square = [
+            Point2::new(-1.0, -1.0),
+            Point2::new(1.0, -1.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(-1.0, 1.0),
+            Point2::new(0.2, 0.3),
+        ];
+        let circle = BoundingCircle::minimum_enclosing(square).unwrap();
+        assert_relative_eq!(circle.center, Point2::origin(), epsilon = 1e-12);
+        assert_relative_eq!(circle.radius, 2f64.sqrt(), max_relative = 1e-12);
+
+        let triangle = [
+            Point2::new(0.0, 0.0),
+            Point2::new(4.0, 0.0),
+            Point2::new(2.0, 0.5),
+        ];
+        let circle = BoundingCircle::minimum_enclosing(triangle).unwrap();
+        assert_relative_eq!(circle.center, Point2::new(2.0, 0.0));
+        assert_relative_eq!(circle.radius, 2.0, max_relative = 1e-12);
+
+        let points = cloud();
+        let exact = BoundingSphere::minimum_enclosing(&points).unwrap();
+        let ritter = BoundingSphere::from_points(&points).unwrap();
+        for p in &points {
+            assert!((p - exact.center).norm() <= exact.radius + 1e-9);
+            assert!(ritter.contains(p));
+        }
+        assert!(exact.radius <= ritter.radius + 1e-12);
+        let on_surface = points
+            .iter()
+            .filter(|p| ((*p - exact.center).norm() - exact.radius).abs() < 1e-9)
+            .count();
+        assert!(on_surface >= 2);
+
+        assert!(BoundingSphere::<f64>::minimum_enclosing(Vec::<Point3<f64>>::new()).is_none());
+        let same = BoundingSphere::minimum_enclosing(vec![Point3::new(1.0, 2.0, 3.0); 4]).unwrap();
+        assert_relative_eq!(same.radius, 0.0);
+    }
+
+    #[test]
+    fn minimum_enclosing_contains_inputs_f32() {
+        let points: Vec<Point3<f32>> = cloud().iter().map(|p| p.cast()).collect();
+        let sphere = BoundingSphere::minimum_enclosing(&points).unwrap();
+        assert!(points.iter().all(|p| sphere.contains(p)));
+
+        let far = [
+            Point2::new(1.0e4f32, 0.1),
+            Point2::new(-1.0e4, -0.1),
+            Point2::new(0.3, 1.0e4),
+        ];
+        let circle = BoundingCircle::minimum_enclosing(far).unwrap();
+        assert!(far.iter().all(|p| circle.contains(p)));
+    }
+
+    #[test]
+    fn growing_and_tests() {
+        let mut sphere = BoundingCircle::from_point(Point2::new(0.0, 0.0));
+        sphere.expand(&Point2::new(2.0, 0.0));
+        assert_relative_eq!(sphere.center, Point2::new(1.0, 0.0));
+        assert_relative_eq!(sphere.radius, 1.0);
+
+        let other = BoundingCircle::new(Point2::new(4.0, 0.0), 1.0);
+        assert!(!sphere.intersects(&other));
+        let merged = sphere.union(&other);
+        assert_relative_eq!(merged.center, Point2::new(2.5, 0.0));
+        assert_relative_eq!(merged.radius, 2.5);
+        assert!(merged.contains_sphere(&sphere) && merged.contains_sphere(&other));
+
+        let space = BoundingSpaceN::new(Point2::new(1.5, 1.5), Point2::new(3.0, 3.0));
+        assert!(!sphere.intersects_space(&space));
+        assert!(merged.intersects_space(&space));
+
+        assert_relative_eq!(
+            BoundingCircle::new(Point2::origin(), 2.0).volume(),
+            4.0 * core::f64::consts::PI
+        );
+        assert_relative_eq!(
+            BoundingSphere::new(Point3::origin(), 2.0).volume(),
+            32.0 / 3.0 * core::f64::consts::PI
+        );
+    }
+
+    #[test]
+    fn space_conversions() {
+        let space = BoundingSpaceN::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 2.0, 2.0));
+        let sphere: BoundingSphere<f64> = space.into();
+        assert_relative_eq!(sphere.center, Point3::new(1.0, 1.0, 1.0));
+        assert_relative_eq!(sphere.radius, 3f64.sqrt());
+        assert!(space.corners().all(|c| sphere.contains(&c)));
+
+        let back: BoundingSpaceN<f64, 3> = sphere.into();
+        assert!(back.contains_space(&space));
+        assert_relative_eq!(back.diagonal()[0], 2.0 * 3f64.sqrt());
+    }
+}
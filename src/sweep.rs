@@ -0,0 +1,393 @@
+//! Sweep-and-prune broadphase returning pairs of overlapping spaces.
+//!
+//! Pairs are reported as `(i, j)` with `i < j` indexing the input. Touching
+//! spaces overlap, as with [`BoundingSpaceN::intersects`], and empty spaces
+//! never do.
+
+use std::collections::HashMap;
+
+use nalgebra::{convert, RealField};
+
+use crate::{total_cmp, BoundingSpaceN};
+
+fn ordered(a: usize, b: usize) -> (usize, usize) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Axis along which the centers of the spaces have the largest variance.
+pub fn widest_axis<T: RealField, const D: usize>(boxes: &[BoundingSpaceN<T, D>]) -> usize {
+    let centers: Vec<_> = boxes
+        .iter()
+        .filter(|b| !b.is_empty())
+        .map(|b| b.center())
+        .collect();
+    if centers.is_empty() {
+        return 0;
+    }
+
+    let count: T = convert(centers.len() as f64);
+    let mean = centers
+        .iter()
+        .fold(nalgebra::SVector::<T, D>::zeros(), |acc, c| acc + &c.coords)
+        / count.to_owned();
+    let variance = centers
+        .iter()
+        .fold(nalgebra::SVector::<T, D>::zeros(), |acc, c| {
+            let delta = &c.coords - &mean;
+            acc + delta.component_mul(&delta)
+        });
+
+    (0..D).fold(0, |best, i| {
+        if variance[i] > variance[best] {
+            i
+        } else {
+            best
+        }
+    })
+}
+
+/// Sorts the spaces by their lower bound on `axis` and sweeps once, testing
+/// the remaining axes for every pair overlapping along it.
+pub fn sweep_and_prune_axis<T: RealField, const D: usize>(
+    boxes: &[BoundingSpaceN<T, D>],
+    axis: usize,
+) -> Vec<(usize, usize)> {
+    let mut order: Vec<usize> = (0..boxes.len()).filter(|&i| !boxes[i].is_empty()).collect();
+    order.sort_by(|&a, &b| total_cmp(&boxes[a].lower[axis], &boxes[b].lower[axis]));
+
+    let mut pairs = Vec::new();
+    let mut active: Vec<usize> = Vec::new();
+
+    for i in order {
+        active.retain(|&a| boxes[a].upper[axis] >= boxes[i].lower[axis]);
+        for &a in &active {
+            if boxes[a].intersects(&boxes[i]) {
+                pairs.push(ordered(a, i));
+            }
+        }
+        active.push(i);
+    }
+
+    pairs
+}
+
+/// Single-axis sweep along the axis of largest center variance.
+pub fn sweep_and_prune<T: RealField, const D: usize>(
+    boxes: &[BoundingSpaceN<T, D>],
+) -> Vec<(usize, usize)> {
+    sweep_and_prune_axis(boxes, widest_axis(boxes))
+}
+
+/// Sweeps every axis independently and keeps the pairs overlapping along
+/// all of them, without any full box test.
+pub fn multi_axis_prune<T: RealField, const D: usize>(
+    boxes: &[BoundingSpaceN<T, D>],
+) -> Vec<(usize, usize)> {
+    let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+
+    for axis in 0..D {
+        let mut order: Vec<usize> = (0..boxes.len()).filter(|&i| !boxes[i].is_empty()).collect();
+        order.sort_by(|&a, &b| total_cmp(&boxes[a].lower[axis], &boxes[b].lower[axis]));
+
+        let mut active: Vec<usize> = Vec::new();
+        for i in order {
+            active.retain(|&a| boxes[a].upper[axis] >= boxes[i].lower[axis]);
+            for &a in &active {
+                *counts.entry(ordered(a, i)).or_default() += 1;
+            }
+            active.push(i);
+        }
+    }
+
+    let mut pairs: Vec<_> = counts
+        .into_iter()
+        .filter(|(_, count)| *count == D)
+        .map(|(pair, _)| pair)
+        .collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+#[derive(Debug, Clone)]
+struct Endpoint<T> {
+    value: T,
+    id: usize,
+    is_lower: bool,
+}
+
+impl<T: RealField> Endpoint<T> {
+    /// Whether `self` sorts before `other`; lower ends go first on ties so
+    /// touching spaces count as overlapping.
+    fn precedes(&self, other: &Self) -> bool {
+        self.value < other.value || (self.value == other.value && self.is_lower && !other.is_lower)
+    }
+}
+
+/// Persistent sweep-and-prune over all axes. Endpoint lists are kept sorted
+/// by moving only the endpoints of changed spaces, which costs little when
+/// spaces move little between updates, and every swap of endpoints adjusts
+/// the number of axes along which the two spaces overlap.
+#[derive(Debug, Clone)]
+pub struct SweepAndPrune<T: RealField, const D: usize> {
+    axes: [Vec<Endpoint<T>>; D],
+    /// Index of the lower and upper endpoint of every id on every axis.
+    positions: Vec<[[usize; 2]; D]>,
+    boxes: Vec<Option<BoundingSpaceN<T, D>>>,
+    free: Vec<usize>,
+    overlaps: HashMap<(usize, usize), usize>,
+}
+
+impl<T: RealField, const D: usize> Default for SweepAndPrune<T, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: RealField, const D: usize> SweepAndPrune<T, D> {
+    pub fn new() -> Self {
+        Self {
+            axes: core::array::from_fn(|_| Vec::new()),
+            positions: Vec::new(),
+            boxes: Vec::new(),
+            free: Vec::new(),
+            overlaps: HashMap::new(),
+        }
+    }
+
+    /// Adds a space and returns the id it is reported under.
+    pub fn insert(&mut self, bound: BoundingSpaceN<T, D>) -> usize {
+        assert!(!bound.is_empty(), "cannot track an empty space");
+
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.boxes[id] = Some(bound.to_owned());
+                id
+            }
+            None => {
+                self.boxes.push(Some(bound.to_owned()));
+                self.positions.push([[0; 2]; D]);
+                self.boxes.len() - 1
+            }
+        };
+
+        for axis in 0..D {
+            let end = self.axes[axis].len();
+            self.axes[axis].push(Endpoint {
+                value: bound.lower[axis].to_owned(),
+                id,
+                is_lower: true,
+            });
+            self.axes[axis].push(Endpoint {
+                value: bound.upper[axis].to_owned(),
+                id,
+                is_lower: false,
+            });
+            self.positions[id][axis] = [end, end + 1];
+            self.settle(axis, id);
+        }
+
+        id
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<BoundingSpaceN<T, D>> {
+        let bound = self.boxes.get_mut(id)?.take()?;
+
+        for axis in 0..D {
+            self.axes[axis].retain(|e| e.id != id);
+            for (index, endpoint) in self.axes[axis].iter().enumerate() {
+                self.positions[endpoint.id][axis][usize::from(!endpoint.is_lower)] = index;
+            }
+        }
+        self.overlaps.retain(|&(a, b), _| a != id && b != id);
+        self.free.push(id);
+
+        Some(bound)
+    }
+
+    pub fn update(&mut self, id: usize, bound: BoundingSpaceN<T, D>) {
+        assert!(!bound.is_empty(), "cannot track an empty space");
+        let slot = self.boxes.get_mut(id).and_then(Option::as_mut);
+        *slot.expect("unknown id") = bound.to_owned();
+
+        for axis in 0..D {
+            let [lower, upper] = self.positions[id][axis];
+            self.axes[axis][lower].value = bound.lower[axis].to_owned();
+            self.axes[axis][upper].value = bound.upper[axis].to_owned();
+            self.settle(axis, id);
+        }
+    }
+
+    pub fn get(&self, id: usize) -> Option<&BoundingSpaceN<T, D>> {
+        self.boxes.get(id).and_then(Option::as_ref)
+    }
+
+    /// Swaps endpoints `j - 1` and `j` of an axis, the one at `j` moving in
+    /// front, and updates the overlap count of their spaces.
+    fn swap_down(&mut self, axis: usize, j: usize) {
+        let endpoints = &mut self.axes[axis];
+        let (moving, passed) = (&endpoints[j], &endpoints[j - 1]);
+        let pair = ordered(moving.id, passed.id);
+
+        if moving.is_lower && !passed.is_lower {
+            *self.overlaps.entry(pair).or_default() += 1;
+        } else if !moving.is_lower && passed.is_lower {
+            if let Some(count) = self.overlaps.get_mut(&pair) {
+                *count -= 1;
+                if *count == 0 {
+                    self.overlaps.remove(&pair);
+                }
+            }
+        }
+
+        endpoints.swap(j, j - 1);
+        for index in [j - 1, j] {
+            let endpoint = &endpoints[index];
+            self.positions[endpoint.id][axis][usize::from(!endpoint.is_lower)] = index;
+        }
+    }
+
+    /// Moves one endpoint of `id` towards the front (`forward == false`) or
+    /// the back until it is in order with its neighbours.
+    fn bubble(&mut self, axis: usize, id: usize, slot: usize, forward: bool) {
+        let mut j = self.positions[id][axis][slot];
+        if forward {
+            while j + 1 < self.axes[axis].len()
+                && self.axes[axis][j + 1].precedes(&self.axes[axis][j])
+            {
+                self.swap_down(axis, j + 1);
+                j += 1;
+            }
+        } else {
+            while j > 0 && self.axes[axis][j].precedes(&self.axes[axis][j - 1]) {
+                self.swap_down(axis, j);
+                j -= 1;
+            }
+        }
+    }
+
+    /// Restores the order of an axis after the endpoints of `id` changed.
+    /// An endpoint never passes the other end of its own space, so the
+    /// lower end leads moves to the front and the upper end moves to the
+    /// back.
+    fn settle(&mut self, axis: usize, id: usize) {
+        self.bubble(axis, id, 0, false);
+        self.bubble(axis, id, 1, false);
+        self.bubble(axis, id, 1, true);
+        self.bubble(axis, id, 0, true);
+    }
+
+    /// Pairs of tracked spaces overlapping along every axis.
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<_> = self
+            .overlaps
+            .iter()
+            .filter(|(_, count)| **count == D)
+            .map(|(pair, _)| *pair)
+            .collect();
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Point3, Vector3};
+
+    use super::*;
+
+    fn scene() -> Vec<BoundingSpaceN<f64, 3>> {
+        (0..60)
+            .map(|i| {
+                let t = i as f64;
+                let lower = Point3::new((t * 1.3).sin() * 8.0, t * 0.4, (t * 0.7).cos() * 2.0);
+                BoundingSpaceN::new(lower, lower + Vector3::new(1.5, 1.0, 1.2))
+            })
+            .collect()
+    }
+
+    fn brute_force(boxes: &[BoundingSpaceN<f64, 3>]) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for i in 0..boxes.len() {
+            for j in i + 1..boxes.len() {
+                if boxes[i].intersects(&boxes[j]) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn stateless_sweeps() {
+        let boxes = scene();
+        let expected = brute_force(&boxes);
+        assert!(!expected.is_empty());
+
+        assert_eq!(widest_axis(&boxes), 1);
+        for axis in 0..3 {
+            let mut pairs = sweep_and_prune_axis(&boxes, axis);
+            pairs.sort_unstable();
+            assert_eq!(pairs, expected);
+        }
+        let mut pairs = sweep_and_prune(&boxes);
+        pairs.sort_unstable();
+        assert_eq!(pairs, expected);
+        assert_eq!(multi_axis_prune(&boxes), expected);
+    }
+
+    #[test]
+    fn persistent_sweep_tracks_motion() {
+        let mut boxes = scene();
+        let mut sap = SweepAndPrune::new();
+        for b in &boxes {
+            sap.insert(*b);
+        }
+        assert_eq!(sap.pairs(), brute_force(&boxes));
+
+        for step in 0..5 {
+            for (i, b) in boxes.iter_mut().enumerate() {
+                let offset = Vector3::new(((i + step) as f64).sin() * 0.3, 0.1, -0.05);
+                b.translate(&offset);
+                sap.update(i, *b);
+            }
+            assert_eq!(sap.pairs(), brute_force(&boxes));
+        }
+
+        // Large jumps past many other spaces, in both directions.
+        for (i, offset) in [(7, 12.0), (8, -9.0), (7, -20.0)] {
+            boxes[i].translate(&Vector3::new(offset, 0.0, 0.0));
+            sap.update(i, boxes[i]);
+            assert_eq!(sap.pairs(), brute_force(&boxes));
+        }
+        let mut grown = boxes[10];
+        grown.lower.y -= 10.0;
+        grown.upper.y += 10.0;
+        boxes[10] = grown;
+        sap.update(10, grown);
+        assert_eq!(sap.pairs(), brute_force(&boxes));
+
+        let removed = sap.remove(3).unwrap();
+        assert!(sap.pairs().iter().all(|&(a, b)| a != 3 && b != 3));
+        assert_eq!(sap.insert(removed), 3);
+        assert_eq!(sap.pairs(), brute_force(&boxes));
+    }
+
+    #[test]
+    fn touching_and_empty() {
+        let a = BoundingSpaceN::new(Point2::new(0.0, 0.0), Point2::new(1.0, 1.0));
+        let b = BoundingSpaceN::new(Point2::new(1.0, 0.0), Point2::new(2.0, 1.0));
+        let boxes = [a, BoundingSpaceN::empty(), b];
+
+        assert_eq!(sweep_and_prune(&boxes), vec![(0, 2)]);
+        assert_eq!(multi_axis_prune(&boxes), vec![(0, 2)]);
+
+        let mut sap = SweepAndPrune::new();
+        sap.insert(b);
+        sap.insert(a);
+        assert_eq!(sap.pairs(), vec![(0, 1)]);
+    }
+}
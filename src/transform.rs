@@ -0,0 +1,184 @@
+use nalgebra::allocator::Allocator;
+use nalgebra::{
+    AbstractRotation, Const, DefaultAllocator, DimNameAdd, DimNameSum, Isometry, Point, RealField,
+    SMatrix, SVector, Similarity, TCategory, Transform, U1,
+};
+
+use crate::BoundingSpaceN;
+
+/// Maps that can produce the axis-aligned bounds of a transformed space.
+pub trait TransformBounds<T: RealField, const D: usize> {
+    fn transform_bounds(&self, bound: &BoundingSpaceN<T, D>) -> BoundingSpaceN<T, D>;
+}
+
+fn linear_part<T: RealField, const D: usize>(
+    transform_vector: impl Fn(&SVector<T, D>) -> SVector<T, D>,
+) -> SMatrix<T, D, D> {
+    let mut matrix = SMatrix::<T, D, D>::zeros();
+
+    for j in 0..D {
+        let mut basis = SVector::<T, D>::zeros();
+        basis[j] = T::one();
+        matrix.set_column(j, &transform_vector(&basis));
+    }
+
+    matrix
+}
+
+impl<T: RealField, R: AbstractRotation<T, D>, const D: usize> TransformBounds<T, D>
+    for Isometry<T, R, D>
+{
+    fn transform_bounds(&self, bound: &BoundingSpaceN<T, D>) -> BoundingSpaceN<T, D> {
+        let matrix = linear_part(|v| self.transform_vector(v));
+        bound.transformed_by_matrix(&matrix, &self.translation.vector)
+    }
+}
+
+impl<T: RealField, R: AbstractRotation<T, D>, const D: usize> TransformBounds<T, D>
+    for Similarity<T, R, D>
+{
+    fn transform_bounds(&self, bound: &BoundingSpaceN<T, D>) -> BoundingSpaceN<T, D> {
+        let matrix = linear_part(|v| self.transform_vector(v));
+        bound.transformed_by_matrix(&matrix, &self.isometry.translation.vector)
+    }
+}
+
+impl<T: RealField, C: TCategory, const D: usize> TransformBounds<T, D> for Transform<T, C, D>
+where
+    Const<D>: DimNameAdd<U1>,
+    DefaultAllocator: Allocator<T, DimNameSum<Const<D>, U1>, DimNameSum<Const<D>, U1>>
+        + Allocator<T, DimNameSum<Const<D>, U1>>,
+{
+    fn transform_bounds(&self, bound: &BoundingSpaceN<T, D>) -> BoundingSpaceN<T, D> {
+        if bound.is_empty() {
+            return BoundingSpaceN::empty();
+        }
+
+        if !C::has_normalizer() {
+            let matrix = self.matrix().fixed_view::<D, D>(0, 0).into_owned();
+            let translation = self.matrix().fixed_view::<D, 1>(0, D).into_owned();
+            return bound.transformed_by_matrix(&matrix, &translation);
+        }
+
+        bound
+            .corners()
+            .map(|corner| self.transform_point(&corner))
+            .collect()
+    }
+}
+
+impl<T: RealField, const D: usize> BoundingSpaceN<T, D> {
+    pub fn transformed_by<M: TransformBounds<T, D>>(&self, transform: &M) -> Self {
+        transform.transform_bounds(self)
+    }
+
+    /// Bounds of `matrix * x + translation` over the space (Arvo's method).
+    pub fn transformed_by_matrix(
+        &self,
+        matrix: &SMatrix<T, D, D>,
+        translation: &SVector<T, D>,
+    ) -> Self {
+        if self.is_empty() {
+            return Self::empty();
+        }
+
+        let center = matrix * self.center().coords + translation;
+        let half_extents = matrix.abs() * self.half_extents();
+
+        Self {
+            lower: (&center - &half_extents).into(),
+            upper: (center + half_extents).into(),
+        }
+    }
+
+    /// All `2^D` corner points of the space.
+    pub fn corners(&self) -> impl Iterator<Item = Point<T, D>> + '_ {
+        (0..1usize << D).map(move |mask| {
+            SVector::<T, D>::from_fn(|i, _| {
+                if mask & (1 << i) == 0 {
+                    self.lower[i].to_owned()
+                } else {
+                    self.upper[i].to_owned()
+                }
+            })
+            .into()
+        })
+    }
+
+    pub fn translate(&mut self, offset: &SVector<T, D>) {
+        if self.is_empty() {
+            return;
+        }
+
+        self.lower += offset;
+        self.upper += offset;
+    }
+
+    pub fn scale_about_center(&mut self, factor: T) {
+        if self.is_empty() {
+            return;
+        }
+
+        let center = self.center();
+        let half_extents = self.half_extents() * factor.abs();
+        self.lower = &center - &half_extents;
+        self.upper = center + half_extents;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Affine2, Isometry2, Matrix3, Point2, Projective2, Similarity2, Vector2};
+
+    use super::*;
+
+    fn unit_square() -> BoundingSpaceN<f64, 2> {
+        BoundingSpaceN::new(Point2::new(-1.0, -1.0), Point2::new(1.0, 1.0))
+    }
+
+    #[test]
+    fn isometry_and_similarity() {
+        let iso = Isometry2::new(Vector2::new(2.0, 0.0), core::f64::consts::FRAC_PI_4);
+        let bound = unit_square().transformed_by(&iso);
+        let r = 2.0_f64.sqrt();
+        assert_relative_eq!(bound.lower, Point2::new(2.0 - r, -r), epsilon = 1e-12);
+        assert_relative_eq!(bound.upper, Point2::new(2.0 + r, r), epsilon = 1e-12);
+
+        let sim = Similarity2::new(Vector2::zeros(), 0.0, 3.0);
+        let bound = unit_square().transformed_by(&sim);
+        assert_relative_eq!(bound.lower, Point2::new(-3.0, -3.0), epsilon = 1e-12);
+        assert_relative_eq!(bound.upper, Point2::new(3.0, 3.0), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn affine_and_projective() {
+        let shear = Matrix3::new(1.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0);
+        let bound = unit_square().transformed_by(&Affine2::from_matrix_unchecked(shear));
+        assert_relative_eq!(bound.lower, Point2::new(-1.0, -1.0));
+        assert_relative_eq!(bound.upper, Point2::new(3.0, 1.0));
+
+        let projective = Matrix3::new(1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.25, 0.0, 1.0);
+        let bound = unit_square().transformed_by(&Projective2::from_matrix_unchecked(projective));
+        let expected: BoundingSpaceN<f64, 2> = unit_square()
+            .corners()
+            .map(|c| Point2::from_homogeneous(projective * c.to_homogeneous()).unwrap())
+            .collect();
+        assert_relative_eq!(bound.lower, expected.lower);
+        assert_relative_eq!(bound.upper, expected.upper);
+    }
+
+    #[test]
+    fn translate_and_scale() {
+        let mut bound = unit_square();
+        bound.translate(&Vector2::new(1.0, 2.0));
+        bound.scale_about_center(0.5);
+        assert_relative_eq!(bound.lower, Point2::new(0.5, 1.5));
+        assert_relative_eq!(bound.upper, Point2::new(1.5, 2.5));
+
+        let mut empty = BoundingSpaceN::<f64, 2>::empty();
+        empty.translate(&Vector2::new(1.0, 2.0));
+        assert!(empty.is_empty());
+        assert!(empty.transformed_by(&Isometry2::identity()).is_empty());
+    }
+}